@@ -0,0 +1,25 @@
+// Signs of a Cloudflare or CAPTCHA wall served in place of the real page.
+// The client checks raw bodies before parsing and the scraper checks parsed
+// documents, so both share one list to keep their verdicts in step.
+
+// Substrings of the raw body, matched before the page is parsed
+pub(crate) const BODY_MARKERS: [&str; 5] = [
+    "<title>Just a moment...</title>",
+    "<title>Attention Required! | Cloudflare</title>",
+    "cf-challenge",
+    "__cf_chl",
+    "/cdn-cgi/challenge-platform/",
+];
+
+// Fragments of the <title> of a parsed document
+pub(crate) const TITLES: [&str; 3] = ["Attention Required", "Just a moment", "Access denied"];
+
+// Elements only present on challenge and CAPTCHA pages
+pub(crate) const SELECTORS: [&str; 6] = [
+    "#challenge-form",
+    "#cf-challenge-running",
+    ".cf-browser-verification",
+    "#challenge-stage",
+    ".g-recaptcha",
+    ".h-captcha",
+];
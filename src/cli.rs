@@ -0,0 +1,132 @@
+use crate::config::Config;
+use crate::error::{ConfigError, Result};
+use std::path::PathBuf;
+
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+const USAGE: &str = "\
+Usage: clutch-scraper [OPTIONS]
+
+Options:
+  --config <PATH>       Path to the configuration file (default: config.toml)
+  --start-path <PATH>   Override the listing path to start scraping from
+  --base-url <URL>      Override the base URL of the site
+  --categories-from-file <PATH>
+                        Scrape every start path listed in the file (one per line)
+  --resume              Resume from the last saved page
+  --dry-run             Validate config and proxies, then exit without scraping
+  --emit-schema <PATH>  Write the JSON Schema of a scraped company to PATH and exit
+  --revalidate          Re-check proxies that were marked dead in a previous run
+  -h, --help            Print this help and exit";
+
+// Command line overrides, applied on top of the file and CLUTCH_* env values
+#[derive(Debug, Default)]
+pub struct CliArgs {
+    pub config: Option<PathBuf>,
+    pub start_path: Option<String>,
+    pub base_url: Option<String>,
+    pub categories_file: Option<String>,
+    pub resume: bool,
+    pub dry_run: bool,
+    pub revalidate: bool,
+    pub emit_schema: Option<PathBuf>,
+    pub help: bool,
+}
+
+impl CliArgs {
+    pub fn from_env() -> Result<Self> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    pub fn parse<I>(args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut parsed = CliArgs::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            // Accept both `--flag value` and `--flag=value`
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => {
+                    (flag.to_string(), Some(value.to_string()))
+                }
+                _ => (arg.clone(), None),
+            };
+
+            match flag.as_str() {
+                "-h" | "--help" => parsed.help = true,
+                "--resume" => parsed.resume = true,
+                "--dry-run" => parsed.dry_run = true,
+                "--revalidate" => parsed.revalidate = true,
+                "--config" => {
+                    parsed.config = Some(PathBuf::from(flag_value(&flag, inline_value, &mut args)?))
+                }
+                "--start-path" => {
+                    parsed.start_path = Some(flag_value(&flag, inline_value, &mut args)?)
+                }
+                "--base-url" => parsed.base_url = Some(flag_value(&flag, inline_value, &mut args)?),
+                "--emit-schema" => {
+                    parsed.emit_schema =
+                        Some(PathBuf::from(flag_value(&flag, inline_value, &mut args)?))
+                }
+                "--categories-from-file" => {
+                    parsed.categories_file = Some(flag_value(&flag, inline_value, &mut args)?)
+                }
+                _ => {
+                    return Err(ConfigError::InvalidArgument(format!(
+                        "unknown argument '{}'\n\n{}",
+                        arg, USAGE
+                    ))
+                    .into())
+                }
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    pub fn config_path(&self) -> PathBuf {
+        self.config
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH))
+    }
+
+    pub fn apply(&self, config: &mut Config) -> Result<()> {
+        if let Some(start_path) = &self.start_path {
+            config.start_path = start_path.clone();
+        }
+        if let Some(base_url) = &self.base_url {
+            config.base_url = base_url.clone();
+        }
+        if let Some(categories_file) = &self.categories_file {
+            config.categories_file = Some(categories_file.clone());
+        }
+        if self.resume {
+            config.resume = true;
+        }
+        if self.dry_run {
+            config.dry_run = true;
+        }
+        if self.revalidate {
+            config.proxy.revalidate = true;
+        }
+
+        config.validate()
+    }
+}
+
+pub fn print_help() {
+    println!("{}", USAGE);
+}
+
+fn flag_value(
+    flag: &str,
+    inline_value: Option<String>,
+    args: &mut impl Iterator<Item = String>,
+) -> Result<String> {
+    inline_value
+        .or_else(|| args.next())
+        .filter(|value| !value.is_empty() && !value.starts_with("--"))
+        .ok_or_else(|| ConfigError::InvalidArgument(format!("{} requires a value", flag)).into())
+}
@@ -1,25 +1,133 @@
 use super::Client;
-use crate::error::{ClientError, Result};
+use crate::config::SUPPORTED_PROXY_SCHEMES;
+use crate::error::{ClientError, ConfigError, Result};
 use http::{
-    header::{HeaderMap, HeaderName},
+    header::{HeaderMap, HeaderName, ACCEPT, ACCEPT_ENCODING, ACCEPT_LANGUAGE},
     HeaderValue,
 };
-use rquest::{Client as RquestClient, Impersonate, Proxy};
+use rquest::{redirect, Client as RquestClient, Impersonate, Proxy};
 use std::str::FromStr;
+use std::time::Duration;
 use url::Url;
 
+const DEFAULT_IMPERSONATION: Impersonate = Impersonate::Chrome131;
+const DEFAULT_TRACE_BODY_CHARS: usize = 500;
+
+// What a browser sends for a top-level navigation
+pub const DEFAULT_ACCEPT: &str =
+    "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,*/*;q=0.8";
+pub const DEFAULT_ACCEPT_LANGUAGE: &str = "en-US,en;q=0.7";
+// Encodings rquest decodes once gzip, brotli and deflate are enabled
+const DEFAULT_ACCEPT_ENCODING: &str = "gzip, deflate, br";
+
+pub const SUPPORTED_IMPERSONATIONS: [&str; 27] = [
+    "chrome",
+    "chrome100",
+    "chrome101",
+    "chrome104",
+    "chrome105",
+    "chrome106",
+    "chrome107",
+    "chrome108",
+    "chrome109",
+    "chrome114",
+    "chrome116",
+    "chrome117",
+    "chrome118",
+    "chrome119",
+    "chrome120",
+    "chrome123",
+    "chrome124",
+    "chrome126",
+    "chrome127",
+    "chrome128",
+    "chrome129",
+    "chrome130",
+    "chrome131",
+    "safari",
+    "safari18",
+    "firefox",
+    "firefox133",
+];
+
+// Maps a `client.impersonate` config value to the rquest fingerprint
+pub fn parse_impersonation(name: &str) -> Result<Impersonate> {
+    let impersonation = match name.trim().to_lowercase().as_str() {
+        "chrome" => DEFAULT_IMPERSONATION,
+        "chrome100" => Impersonate::Chrome100,
+        "chrome101" => Impersonate::Chrome101,
+        "chrome104" => Impersonate::Chrome104,
+        "chrome105" => Impersonate::Chrome105,
+        "chrome106" => Impersonate::Chrome106,
+        "chrome107" => Impersonate::Chrome107,
+        "chrome108" => Impersonate::Chrome108,
+        "chrome109" => Impersonate::Chrome109,
+        "chrome114" => Impersonate::Chrome114,
+        "chrome116" => Impersonate::Chrome116,
+        "chrome117" => Impersonate::Chrome117,
+        "chrome118" => Impersonate::Chrome118,
+        "chrome119" => Impersonate::Chrome119,
+        "chrome120" => Impersonate::Chrome120,
+        "chrome123" => Impersonate::Chrome123,
+        "chrome124" => Impersonate::Chrome124,
+        "chrome126" => Impersonate::Chrome126,
+        "chrome127" => Impersonate::Chrome127,
+        "chrome128" => Impersonate::Chrome128,
+        "chrome129" => Impersonate::Chrome129,
+        "chrome130" => Impersonate::Chrome130,
+        "chrome131" => Impersonate::Chrome131,
+        "safari" | "safari18" => Impersonate::Safari18,
+        "firefox" | "firefox133" => Impersonate::Firefox133,
+        _ => {
+            return Err(ConfigError::InvalidValue(format!(
+                "Unknown client.impersonate value '{}'; supported: {}",
+                name,
+                SUPPORTED_IMPERSONATIONS.join(", ")
+            ))
+            .into())
+        }
+    };
+    Ok(impersonation)
+}
+
+// How far a client follows redirects. Redirects that aren't followed come back
+// as a 3xx `ClientResponse` with its `Location`; going past a limit is an error.
+// `Limited(0)` behaves like `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RedirectPolicy {
+    None,
+    Limited(usize),
+    #[default]
+    Default,
+}
+
 #[derive(Default)]
 pub struct ClientBuilder {
     base_url: Option<String>,
     proxy: Option<String>,
-    chrome_impersonation: bool,
+    impersonation: Option<Impersonate>,
+    cookie_store: bool,
     headers: HeaderMap,
+    timeout: Option<Duration>,
+    redirect: RedirectPolicy,
+    trace_body_chars: usize,
 }
 
 impl ClientBuilder {
+    // Starts with browser-like `Accept` and `Accept-Language` headers, which
+    // `header` can override
     pub fn new() -> Self {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_static(DEFAULT_ACCEPT));
+        headers.insert(
+            ACCEPT_LANGUAGE,
+            HeaderValue::from_static(DEFAULT_ACCEPT_LANGUAGE),
+        );
+
         Self {
-            headers: HeaderMap::new(),
+            headers,
+            cookie_store: true,
+            trace_body_chars: DEFAULT_TRACE_BODY_CHARS,
             ..Default::default()
         }
     }
@@ -34,8 +142,36 @@ impl ClientBuilder {
         self
     }
 
+    pub fn impersonate(mut self, impersonation: Impersonate) -> Self {
+        self.impersonation = Some(impersonation);
+        self
+    }
+
+    // Shorthand for impersonating the default Chrome version
     pub fn chrome_impersonation(mut self, enabled: bool) -> Self {
-        self.chrome_impersonation = enabled;
+        self.impersonation = enabled.then_some(DEFAULT_IMPERSONATION);
+        self
+    }
+
+    // Keep cookies (e.g. challenge cookies) between requests made through the same client
+    pub fn cookie_store(mut self, enabled: bool) -> Self {
+        self.cookie_store = enabled;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn redirect(mut self, policy: RedirectPolicy) -> Self {
+        self.redirect = policy;
+        self
+    }
+
+    // Characters of the response body logged per request at TRACE level
+    pub fn trace_body_chars(mut self, chars: usize) -> Self {
+        self.trace_body_chars = chars;
         self
     }
 
@@ -65,23 +201,70 @@ impl ClientBuilder {
 
         let mut client_builder = RquestClient::builder();
 
-        if let Some(proxy_url) = self.proxy {
-            client_builder = client_builder.proxy(Proxy::all(&proxy_url).map_err(|e| {
+        if let Some(proxy_url) = &self.proxy {
+            let scheme = Url::parse(proxy_url)
+                .map_err(|e| ClientError::InvalidUrl(format!("Invalid proxy URL: {}", e)))?
+                .scheme()
+                .to_string();
+            if !SUPPORTED_PROXY_SCHEMES.contains(&scheme.as_str()) {
+                return Err(ClientError::BuildError(format!(
+                    "Unsupported proxy scheme: {}",
+                    scheme
+                ))
+                .into());
+            }
+
+            // `Proxy::all` routes HTTPS targets through an HTTP proxy with a
+            // CONNECT tunnel, so the TLS handshake happens end to end
+            client_builder = client_builder.proxy(Proxy::all(proxy_url).map_err(|e| {
                 ClientError::BuildError(format!("Failed to configure proxy: {}", e))
             })?);
         }
 
-        if self.chrome_impersonation {
-            client_builder = client_builder.impersonate(Impersonate::Chrome131);
+        if let Some(timeout) = self.timeout {
+            client_builder = client_builder.timeout(timeout);
+        }
+
+        client_builder = client_builder
+            .cookie_store(self.cookie_store)
+            .gzip(true)
+            .brotli(true)
+            .deflate(true);
+
+        client_builder = client_builder.redirect(match self.redirect {
+            RedirectPolicy::None | RedirectPolicy::Limited(0) => redirect::Policy::none(),
+            RedirectPolicy::Limited(max) => redirect::Policy::limited(max),
+            RedirectPolicy::Default => redirect::Policy::default(),
+        });
+
+        if let Some(impersonation) = self.impersonation {
+            client_builder = client_builder.impersonate(impersonation);
         }
 
         let mut inner = client_builder
             .build()
             .map_err(|e| ClientError::BuildError(format!("Failed to build client: {}", e)))?;
 
-        // Set the headers on the client
-        *inner.as_mut().headers() = self.headers;
+        // Set the headers on the client. Keep the impersonated browser's
+        // Accept-Encoding so the fingerprint stays consistent and only fall
+        // back to our own when it has none.
+        let mut headers = self.headers;
+        if !headers.contains_key(ACCEPT_ENCODING) {
+            let encoding = inner
+                .headers()
+                .get(ACCEPT_ENCODING)
+                .cloned()
+                .unwrap_or(HeaderValue::from_static(DEFAULT_ACCEPT_ENCODING));
+            headers.insert(ACCEPT_ENCODING, encoding);
+        }
+        *inner.as_mut().headers() = headers;
 
-        Ok(Client { inner, base_url })
+        Ok(Client {
+            inner,
+            base_url,
+            proxy: self.proxy,
+            timeout: self.timeout,
+            trace_body_chars: self.trace_body_chars,
+        })
     }
 }
@@ -1,19 +1,151 @@
 mod builder;
+#[cfg(test)]
+mod tests;
 
-use crate::error::{ClientError, Result};
-pub use builder::ClientBuilder;
-use rquest::Client as RquestClient;
+use crate::block_page;
+use crate::error::{AppError, ClientError, Result};
+use crate::utils;
+use crate::{log_debug, log_trace, log_warn};
+pub use builder::{parse_impersonation, ClientBuilder, RedirectPolicy, DEFAULT_ACCEPT_LANGUAGE};
+use flate2::read::GzDecoder;
+use http::header::{self, HeaderMap};
+use rand::seq::SliceRandom;
+use rquest::{Client as RquestClient, Method};
+use std::io::Read;
+use std::time::Duration;
 use url::Url;
 
+pub const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/132.0.0.0 Safari/537.36";
+
+// Picks a random user agent, preferring ones that match the impersonated browser
+// (e.g. "chrome124" -> "Chrome/124.", then any "Chrome/") so the UA and TLS
+// fingerprint don't contradict each other
+pub fn pick_user_agent(user_agents: &[String], impersonate: &str) -> String {
+    let impersonate = impersonate.trim().to_lowercase();
+    let family_len = impersonate
+        .find(|c: char| c.is_ascii_digit())
+        .unwrap_or(impersonate.len());
+    let (family, version) = impersonate.split_at(family_len);
+
+    let family_token = match family {
+        "chrome" => "Chrome/",
+        "safari" => "Version/",
+        "firefox" => "Firefox/",
+        _ => "",
+    };
+    let matches_family =
+        |ua: &&String| ua.contains(family_token) && (family != "safari" || !ua.contains("Chrome/"));
+    let version_token = format!("{}{}", family_token, version);
+
+    let exact: Vec<&String> = user_agents
+        .iter()
+        .filter(matches_family)
+        .filter(|ua| !version.is_empty() && ua.contains(&version_token))
+        .collect();
+    let same_family: Vec<&String> = user_agents.iter().filter(matches_family).collect();
+
+    let mut rng = rand::thread_rng();
+    [exact, same_family, user_agents.iter().collect()]
+        .into_iter()
+        .find(|candidates| !candidates.is_empty())
+        .and_then(|candidates| candidates.choose(&mut rng).map(|ua| ua.to_string()))
+        .unwrap_or_else(|| DEFAULT_USER_AGENT.to_string())
+}
+
 #[derive(Debug)]
 pub struct ClientResponse {
     pub status: u16,
     pub content: String,
+    // URL the response came from once redirects were followed
+    pub final_url: String,
+    // Target of a 3xx the client was not allowed to follow
+    pub location: Option<String>,
 }
 
+impl ClientResponse {
+    // Cloudflare/CAPTCHA interstitials are often served with a 200
+    pub fn challenge_marker(&self) -> Option<&'static str> {
+        block_page::BODY_MARKERS
+            .iter()
+            .find(|marker| self.content.contains(*marker))
+            .copied()
+    }
+
+    pub fn is_challenge_page(&self) -> bool {
+        self.challenge_marker().is_some()
+    }
+
+    pub fn is_redirect(&self) -> bool {
+        (300..400).contains(&self.status)
+    }
+}
+
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(30);
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// Some proxies strip `Content-Encoding` but pass the compressed body through,
+// which rquest then hands back undecoded. Catch gzip by its magic bytes so the
+// saved HTML isn't garbage; anything else is read as (lossy) UTF-8.
+pub(crate) fn decode_body(bytes: &[u8]) -> Result<String> {
+    if !bytes.starts_with(&GZIP_MAGIC) {
+        return Ok(String::from_utf8_lossy(bytes).into_owned());
+    }
+
+    let mut content = String::new();
+    GzDecoder::new(bytes)
+        .read_to_string(&mut content)
+        .map_err(|e| ClientError::Decode(format!("Invalid gzip body: {}", e)))?;
+    log_debug!(
+        "[client] Decoded a gzip body of {} bytes the proxy passed through undecoded",
+        bytes.len()
+    );
+    Ok(content)
+}
+
+// Never written to the trace log, even when every header is being dumped
+const REDACTED_HEADERS: [header::HeaderName; 4] = [
+    header::COOKIE,
+    header::SET_COOKIE,
+    header::AUTHORIZATION,
+    header::PROXY_AUTHORIZATION,
+];
+
+fn redacted_headers(headers: &HeaderMap) -> String {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if REDACTED_HEADERS.contains(name) {
+                "[redacted]"
+            } else {
+                value.to_str().unwrap_or("[binary]")
+            };
+            format!("{}: {}", name, value)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Blocks, refused tunnels and redirect loops won't clear up by asking the same
+// proxy again
+fn is_retryable_on_same_proxy(error: &AppError) -> bool {
+    match error {
+        AppError::Client(ClientError::Status(403))
+        | AppError::Client(ClientError::ProxyTunnelRefused { .. })
+        | AppError::Client(ClientError::TooManyRedirects(_)) => false,
+        _ => error.is_transient(),
+    }
+}
+
+// Clones share the connection pool and cookie store
+#[derive(Clone)]
 pub struct Client {
     inner: RquestClient,
     base_url: String,
+    proxy: Option<String>,
+    // Applied to every request, so it can change without rebuilding the client
+    timeout: Option<Duration>,
+    trace_body_chars: usize,
 }
 
 impl Client {
@@ -21,9 +153,93 @@ impl Client {
         ClientBuilder::new()
     }
 
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub async fn get(&self, path: &str) -> Result<ClientResponse> {
+        self.get_with_headers(path, HeaderMap::new()).await
+    }
+
+    // GET with `extra` headers merged over the client defaults for this request
+    // only, e.g. a `Referer` that follows the pagination
+    pub async fn get_with_headers(&self, path: &str, extra: HeaderMap) -> Result<ClientResponse> {
+        let url = self.build_url(path)?;
+        self.request(Method::GET, &url, extra, None).await
+    }
+
+    // Headers only, for cheap reachability checks; `content` is empty
+    pub async fn head(&self, path: &str) -> Result<ClientResponse> {
+        let url = self.build_url(path)?;
+        self.request(Method::HEAD, &url, HeaderMap::new(), None)
+            .await
+    }
+
+    // POSTs `body` serialized as JSON, e.g. to a webhook
+    pub async fn post_json(
+        &self,
+        path: &str,
+        body: &impl serde::Serialize,
+    ) -> Result<ClientResponse> {
         let url = self.build_url(path)?;
-        self.request(&url).await
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static("application/json"),
+        );
+        let body = serde_json::to_vec(body)?;
+        self.request(Method::POST, &url, headers, Some(body)).await
+    }
+
+    // Retries on the same proxy with backoff: up to `retries` times for
+    // transient failures (timeouts, dropped connections) and up to
+    // `server_error_retries` times for 5xx responses. Anything else, including
+    // a 403, is returned right away so the caller can switch proxies. Also
+    // returns the number of attempts made.
+    pub async fn get_with_retries(
+        &self,
+        path: &str,
+        extra: &HeaderMap,
+        retries: u32,
+        server_error_retries: u32,
+        backoff: Duration,
+    ) -> Result<(ClientResponse, u32)> {
+        let mut attempt = 0;
+        let mut transient_failures = 0;
+        let mut server_errors = 0;
+        loop {
+            attempt += 1;
+            let e = match self.get_with_headers(path, extra.clone()).await {
+                Ok(response) => return Ok((response, attempt)),
+                Err(e) => e,
+            };
+
+            let retry = match &e {
+                AppError::Client(ClientError::Status(500..=599)) => {
+                    server_errors += 1;
+                    server_errors <= server_error_retries
+                }
+                e if is_retryable_on_same_proxy(e) => {
+                    transient_failures += 1;
+                    transient_failures <= retries
+                }
+                _ => false,
+            };
+            if !retry {
+                return Err(e);
+            }
+
+            let wait = utils::backoff(attempt - 1, backoff, MAX_RETRY_BACKOFF);
+            log_warn!(
+                "[client] Transient error on attempt {} for {}: {}, retrying in {:.1?}",
+                attempt,
+                path,
+                e,
+                wait
+            );
+            tokio::time::sleep(wait).await;
+        }
     }
 
     fn build_url(&self, path: &str) -> Result<String> {
@@ -39,28 +255,145 @@ impl Client {
         Ok(full_url.to_string())
     }
 
-    async fn request(&self, url: &str) -> Result<ClientResponse> {
-        let response = self
-            .inner
-            .get(url)
+    // Proxies that refuse CONNECT surface deep in the error chain, so look for
+    // the tunnel failure there to give a clearer message than "request failed"
+    fn classify_send_error(&self, error: &rquest::Error) -> ClientError {
+        const TUNNEL_ERRORS: [&str; 3] = [
+            "unsuccessful tunnel",
+            "unexpected eof while tunneling",
+            "proxy authentication required",
+        ];
+
+        if let Some(proxy) = &self.proxy {
+            let mut source: Option<&dyn std::error::Error> = Some(error);
+            while let Some(err) = source {
+                let message = err.to_string();
+                if TUNNEL_ERRORS.iter().any(|t| message.contains(t)) {
+                    return ClientError::ProxyTunnelRefused {
+                        proxy: proxy.clone(),
+                        reason: message,
+                    };
+                }
+                source = err.source();
+            }
+        }
+
+        classify_rquest_error(error)
+    }
+
+    async fn request(
+        &self,
+        method: Method,
+        url: &str,
+        extra: HeaderMap,
+        body: Option<Vec<u8>>,
+    ) -> Result<ClientResponse> {
+        // Formatting headers and copying the body is only worth it when the
+        // output is actually going somewhere
+        let trace = tracing::enabled!(tracing::Level::TRACE);
+        if trace {
+            let mut headers = self.inner.headers().clone();
+            headers.extend(extra.clone());
+            log_trace!(
+                "[client] {} {} via {} [{}]",
+                method,
+                url,
+                self.proxy.as_deref().unwrap_or("direct"),
+                redacted_headers(&headers)
+            );
+        }
+
+        let mut request = self.inner.request(method, url).headers(extra);
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
+        if let Some(body) = body {
+            request = request.body(body);
+        }
+        let response = request
             .send()
             .await
-            .map_err(|e| ClientError::RequestFailed(e.to_string()))?;
+            .map_err(|e| self.classify_send_error(&e))?;
 
         let status = response.status().as_u16();
         let is_success = response.status().is_success();
-        let content = response.text().await.map_err(|e| {
-            ClientError::RequestFailed(format!("Failed to get response text: {}", e))
-        })?;
-
-        if !is_success {
-            return Err(ClientError::ResponseError {
-                status_code: status,
-                message: String::new(),
+        let is_redirect = response.status().is_redirection();
+        let location = response
+            .headers()
+            .get(header::LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let final_url = response.url().to_string();
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| classify_rquest_error(&e))?;
+        let content = decode_body(&body)?;
+
+        if trace {
+            let snippet: String = content.chars().take(self.trace_body_chars).collect();
+            log_trace!(
+                "[client] {} from {} ({} bytes): {}",
+                status,
+                final_url,
+                content.len(),
+                snippet
+            );
+        }
+
+        // A 3xx only reaches this point when the redirect policy stopped it
+        if !is_success && !is_redirect {
+            return Err(ClientError::Status(status).into());
+        }
+
+        Ok(ClientResponse {
+            status,
+            content,
+            final_url,
+            location: location.filter(|_| is_redirect),
+        })
+    }
+}
+
+// Maps an rquest error onto a structured variant so callers can tell
+// transient failures from permanent ones
+fn classify_rquest_error(error: &rquest::Error) -> ClientError {
+    const TLS_MESSAGES: [&str; 4] = ["tls", "ssl", "certificate", "handshake"];
+    const RESET_KINDS: [std::io::ErrorKind; 4] = [
+        std::io::ErrorKind::ConnectionReset,
+        std::io::ErrorKind::ConnectionAborted,
+        std::io::ErrorKind::BrokenPipe,
+        std::io::ErrorKind::UnexpectedEof,
+    ];
+
+    let message = error.to_string();
+    if error.is_timeout() {
+        return ClientError::Timeout(message);
+    }
+
+    let mut source: Option<&dyn std::error::Error> = Some(error);
+    while let Some(err) = source {
+        if let Some(io_error) = err.downcast_ref::<std::io::Error>() {
+            if RESET_KINDS.contains(&io_error.kind()) {
+                return ClientError::Connect(message);
             }
-            .into());
         }
+        let lower = err.to_string().to_lowercase();
+        if TLS_MESSAGES.iter().any(|m| lower.contains(m)) {
+            return ClientError::Tls(message);
+        }
+        source = err.source();
+    }
 
-        Ok(ClientResponse { status, content })
+    if error.is_connect() {
+        ClientError::Connect(message)
+    } else if error.is_redirect() {
+        ClientError::TooManyRedirects(message)
+    } else if error.is_decode() || error.is_body() {
+        ClientError::Decode(message)
+    } else if let Some(status) = error.status() {
+        ClientError::Status(status.as_u16())
+    } else {
+        ClientError::RequestFailed(message)
     }
 }
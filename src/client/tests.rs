@@ -0,0 +1,63 @@
+use super::{decode_body, ClientBuilder};
+use crate::error::ClientError;
+use std::path::Path;
+
+fn fixture_bytes(name: &str) -> Vec<u8> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    std::fs::read(&path).unwrap_or_else(|e| panic!("reading {:?}: {}", path, e))
+}
+
+#[test]
+fn decodes_gzip_body_passed_through_by_proxy() {
+    let expected = String::from_utf8(fixture_bytes("listing.html")).unwrap();
+    let decoded = decode_body(&fixture_bytes("listing.html.gz")).unwrap();
+    assert_eq!(decoded, expected);
+}
+
+#[test]
+fn leaves_plain_body_untouched() {
+    let html = fixture_bytes("listing.html");
+    assert_eq!(
+        decode_body(&html).unwrap(),
+        String::from_utf8(html).unwrap()
+    );
+}
+
+#[test]
+fn only_missing_pages_count_as_page_errors() {
+    for error in [ClientError::Status(404), ClientError::Status(410)] {
+        assert!(error.is_page_error(), "{:?}", error);
+    }
+    // Proxy-side failures switch proxies instead of ending the crawl
+    for error in [
+        ClientError::Tls("handshake failure".to_string()),
+        ClientError::Decode("truncated body".to_string()),
+        ClientError::RequestFailed("unknown".to_string()),
+        ClientError::Connect("reset".to_string()),
+        ClientError::Status(403),
+        ClientError::Status(502),
+    ] {
+        assert!(!error.is_page_error(), "{:?}", error);
+    }
+}
+
+#[test]
+fn builds_client_for_https_target_through_http_proxy() {
+    // Nothing is sent: the CONNECT tunnel is only opened on the first request
+    let client = ClientBuilder::new()
+        .base_url("https://clutch.co")
+        .proxy("http://127.0.0.1:8080")
+        .chrome_impersonation(true)
+        .build()
+        .unwrap();
+    assert_eq!(client.base_url, "https://clutch.co");
+    assert_eq!(client.proxy.as_deref(), Some("http://127.0.0.1:8080"));
+
+    let unsupported = ClientBuilder::new()
+        .base_url("https://clutch.co")
+        .proxy("ftp://127.0.0.1:21")
+        .build();
+    assert!(unsupported.is_err());
+}
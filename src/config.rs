@@ -1,7 +1,11 @@
 use crate::error::{ConfigError, Result};
+use crate::scraper::CompanyData;
+use crate::utils;
 use serde::Deserialize;
 use std::path::Path;
+use std::time::Duration;
 use tracing::info;
+use url::Url;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct LogConfig {
@@ -11,12 +15,37 @@ pub struct LogConfig {
     pub directory: String,
     #[serde(default = "default_log_filename")]
     pub filename: String,
+    #[serde(default)]
+    pub error_filename: Option<String>,
+    #[serde(default)]
+    pub format: LogFormat,
+    #[serde(default)]
+    pub max_log_size_mb: u64,
+    #[serde(default = "default_max_log_files")]
+    pub max_log_files: usize,
+    // Mirror log lines to stdout; services may rely on the log file alone
+    #[serde(default = "default_log_stdout")]
+    pub stdout: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Pretty,
+    Json,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct ProxyConfig {
     #[serde(default = "default_proxy_file")]
     pub file: String,
+    #[serde(default = "default_proxy_scheme")]
+    pub scheme: String,
+    // One upstream proxy (e.g. "http://host:port") used for every request
+    // instead of rotating through `file`
+    #[serde(default)]
+    pub single: Option<String>,
     #[serde(default = "default_proxy_switch_delay")]
     pub switch_delay: u64,
     #[serde(default = "default_proxy_max_retries")]
@@ -25,6 +54,251 @@ pub struct ProxyConfig {
     pub request_timeout: u64,
     #[serde(default = "default_proxy_concurrent_validations")]
     pub concurrent_validations: usize,
+    #[serde(default)]
+    pub stats_file: Option<String>,
+    // host:port entries, one per line, never used regardless of validation
+    #[serde(default)]
+    pub blacklist_file: Option<String>,
+    #[serde(default)]
+    pub report_file: Option<String>,
+    #[serde(default)]
+    pub revalidate: bool,
+    #[serde(default)]
+    pub resurrection_interval: u64,
+    #[serde(default)]
+    pub adaptive_timeout: bool,
+    #[serde(default = "default_proxy_timeout_multiplier")]
+    pub timeout_multiplier: f64,
+    #[serde(default = "default_proxy_min_timeout")]
+    pub min_timeout: u64,
+    #[serde(default = "default_proxy_max_timeout")]
+    pub max_timeout: u64,
+    // ISO country codes of acceptable exit IPs (empty allows any country)
+    #[serde(default)]
+    pub allowed_countries: Vec<String>,
+    // Returns the exit IP's country, as plain text or JSON with a `country` field
+    #[serde(default = "default_proxy_geo_lookup_url")]
+    pub geo_lookup_url: String,
+    #[serde(default)]
+    pub selection: ProxySelectionConfig,
+    // Validations between progress log lines (0 disables)
+    #[serde(default = "default_proxy_progress_interval")]
+    pub progress_interval: usize,
+    // Requests one proxy may have in flight at once (0 = no limit)
+    #[serde(default = "default_proxy_max_concurrent_per_proxy")]
+    pub max_concurrent_per_proxy: usize,
+    // GET base_url once through every working proxy before the crawl
+    #[serde(default)]
+    pub warmup: bool,
+    // Try socks5, http and https in turn during validation when a proxy can't
+    // be reached with its listed scheme, and crawl with the one that worked
+    #[serde(default)]
+    pub autodetect_scheme: bool,
+}
+
+// Weights of the score `get_proxy` minimizes:
+// failure_weight * failures + latency_weight * latency_secs
+// + success_weight * (1 - success_rate) + jitter * random(0..1)
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProxySelectionConfig {
+    #[serde(default = "default_selection_failure_weight")]
+    pub failure_weight: f64,
+    #[serde(default = "default_selection_latency_weight")]
+    pub latency_weight: f64,
+    #[serde(default = "default_selection_success_weight")]
+    pub success_weight: f64,
+    #[serde(default = "default_selection_jitter")]
+    pub jitter: f64,
+    // Requests every proxy needs before scoring replaces least-recently-used
+    #[serde(default = "default_selection_min_requests")]
+    pub min_requests: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RetryConfig {
+    // Falls back to `proxy.switch_delay` when unset
+    #[serde(default)]
+    pub base_delay: Option<u64>,
+    #[serde(default = "default_retry_max_delay")]
+    pub max_delay: u64,
+    // Retries on the same proxy for timeouts and dropped connections
+    #[serde(default = "default_retry_transient_retries")]
+    pub transient_retries: u32,
+    // Retries on the same proxy for 5xx responses, before the proxy is blamed
+    #[serde(default = "default_retry_server_error_retries")]
+    pub server_error_retries: u32,
+    // Sliding window for the `max_retries` cap on page retries, shared by all pages
+    #[serde(default = "default_retry_window_secs")]
+    pub window_secs: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScraperConfig {
+    // Fraction of provider cards that must parse for a page to count (0.0 disables)
+    #[serde(default)]
+    pub min_success_ratio: f64,
+    // 200 responses shorter than this many bytes are treated as truncated (0 disables)
+    #[serde(default = "default_scraper_min_content_length")]
+    pub min_content_length: usize,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ProcessingConfig {
+    // Threads parsing saved pages in parallel (0 uses one per CPU core)
+    #[serde(default)]
+    pub workers: usize,
+}
+
+// Prometheus text endpoint served at http://0.0.0.0:<port>/metrics
+#[derive(Debug, Clone, Deserialize)]
+pub struct MetricsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_metrics_port")]
+    pub port: u16,
+}
+
+// JSON liveness endpoint at http://0.0.0.0:<metrics.port>/health, served by
+// the same task as the metrics
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HealthConfig {
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+// CSS selectors that tend to change with Clutch's markup, overridable without a rebuild
+#[derive(Debug, Clone, Deserialize)]
+pub struct SelectorConfig {
+    // Candidates for the listing container, tried in order; the first that
+    // matches is used
+    #[serde(default = "default_providers_list_selectors")]
+    pub providers_list: Vec<String>,
+    #[serde(default = "default_review_container_selector")]
+    pub review_container: String,
+    #[serde(default = "default_review_quote_selector")]
+    pub review_quote: String,
+    #[serde(default = "default_review_reviewer_selector")]
+    pub review_reviewer: String,
+    #[serde(default = "default_review_role_selector")]
+    pub review_role: String,
+    // "Top ... Companies" ranking badges on a provider card
+    #[serde(default = "default_awards_selector")]
+    pub awards: String,
+    #[serde(default = "default_verified_badge_selector")]
+    pub verified_badge: String,
+    // Card classes marking a paid placement rather than an organic listing
+    #[serde(default = "default_sponsored_classes")]
+    pub sponsored_classes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StorageConfig {
+    // Save downloaded pages as gzip (`rust-page-N.html.gz`)
+    #[serde(default)]
+    pub compress: bool,
+    #[serde(default = "default_storage_html_dir")]
+    pub html_dir: String,
+    // Per-company JSON files (output.mode = "per_company")
+    #[serde(default = "default_storage_json_dir")]
+    pub json_dir: String,
+    #[serde(default)]
+    pub checkpoint_file: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimitConfig {
+    // Requests per minute across all proxies; 0 disables the limit
+    #[serde(default)]
+    pub requests_per_minute: u32,
+    #[serde(default = "default_rate_limit_burst")]
+    pub burst: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClientConfig {
+    // Browser fingerprint to impersonate, e.g. "chrome131", "chrome124", "safari"
+    #[serde(default = "default_client_impersonate")]
+    pub impersonate: String,
+    // User agents to rotate between; one is picked for each new client
+    #[serde(default)]
+    pub user_agents: Vec<String>,
+    // Characters of each response body included in TRACE-level logs
+    #[serde(default = "default_trace_body_chars")]
+    pub trace_body_chars: usize,
+    // Sent as `Accept-Language`; Clutch localizes some listings by it
+    #[serde(default = "default_client_accept_language")]
+    pub accept_language: String,
+    // Redirects followed per request; 0 returns the 3xx so the proxy can be switched
+    #[serde(default = "default_client_max_redirects")]
+    pub max_redirects: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Json,
+    Csv,
+    Both,
+    Jsonl,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputMode {
+    PerCompany,
+    Aggregate,
+}
+
+// Where extracted companies are sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputSinkKind {
+    File,
+    Webhook,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RedactMode {
+    Blank,
+    Hash,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OutputConfig {
+    #[serde(default = "default_output_format")]
+    pub format: OutputFormat,
+    #[serde(default)]
+    pub formats: Vec<OutputFormat>,
+    #[serde(default = "default_output_mode")]
+    pub mode: OutputMode,
+    #[serde(default = "default_output_json_file")]
+    pub json_file: String,
+    #[serde(default = "default_output_csv_file")]
+    pub csv_file: String,
+    #[serde(default = "default_output_jsonl_file")]
+    pub jsonl_file: String,
+    #[serde(default)]
+    pub redact_fields: Vec<String>,
+    #[serde(default = "default_output_redact_mode")]
+    pub redact_mode: RedactMode,
+    // Incremental mode: companies from the last run, diffed against to write
+    // only new and modified companies. Replaced with this run's full set.
+    #[serde(default)]
+    pub previous_file: Option<String>,
+    // Additions, removals and modifications found in incremental mode
+    #[serde(default = "default_output_changes_file")]
+    pub changes_file: String,
+    #[serde(default = "default_output_sink")]
+    pub sink: OutputSinkKind,
+    // Required by the webhook sink, which POSTs companies there as JSON arrays
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    #[serde(default = "default_output_webhook_batch_size")]
+    pub webhook_batch_size: usize,
+    // Retries per batch after the first failed POST
+    #[serde(default = "default_output_webhook_retries")]
+    pub webhook_retries: u32,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -38,6 +312,11 @@ pub struct Config {
     #[serde(default = "default_max_retries")]
     pub max_retries: u32,
 
+    // Abort the download phase after this many failed fetch attempts in a row,
+    // across pages and proxies (0 disables)
+    #[serde(default)]
+    pub abort_after_consecutive_failures: usize,
+
     #[serde(default = "default_retry_delay")]
     pub retry_delay: u64,
 
@@ -47,6 +326,62 @@ pub struct Config {
     #[serde(default = "default_start_path")]
     pub start_path: String,
 
+    // 1-based listing page the crawl starts from, added to `start_path` as `?page=`
+    #[serde(default = "default_start_page")]
+    pub start_page: usize,
+
+    // File with one start path per line; each is scraped in turn into its own
+    // output directories instead of `start_path`
+    #[serde(default)]
+    pub categories_file: Option<String>,
+
+    #[serde(default)]
+    pub initial_delay_secs: u64,
+
+    // Pause after each successful page, picked at random from [min, max] seconds
+    #[serde(default = "default_request_delay")]
+    pub request_delay_min: u64,
+
+    #[serde(default = "default_request_delay")]
+    pub request_delay_max: u64,
+
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+
+    #[serde(default)]
+    pub resume: bool,
+
+    // Stop after fetching this many pages in one run (resumed pages don't count)
+    #[serde(default)]
+    pub max_pages: Option<usize>,
+
+    // Stop processing once this many (unique, if dedupe is on) companies are collected
+    #[serde(default)]
+    pub max_companies: Option<usize>,
+
+    // JSON summary of the run (timestamps, volumes, proxy counts) written at the end
+    #[serde(default)]
+    pub summary_file: Option<String>,
+
+    // Wall-clock cap in seconds from startup; downloads stop once it passes and
+    // the pages saved so far are still processed
+    #[serde(default)]
+    pub run_deadline_secs: Option<u64>,
+
+    // Validate config and proxies, then exit without downloading anything
+    #[serde(default)]
+    pub dry_run: bool,
+
+    // Check that base_url answers through a validated proxy before crawling
+    #[serde(default = "default_preflight")]
+    pub preflight: bool,
+
+    #[serde(default)]
+    pub expected_per_page: usize,
+
+    #[serde(default = "default_underfill_threshold")]
+    pub underfill_threshold: f64,
+
     #[serde(default = "default_proxy_max_retries")]
     pub proxy_max_retries: u32,
 
@@ -56,11 +391,83 @@ pub struct Config {
     #[serde(default = "default_proxy_concurrent_validations")]
     pub proxy_concurrent_validations: usize,
 
+    #[serde(default)]
+    pub min_companies_for_success: usize,
+
+    #[serde(default = "default_dedupe")]
+    pub dedupe: bool,
+
     #[serde(default)]
     pub logging: LogConfig,
 
     #[serde(default)]
     pub proxy: ProxyConfig,
+
+    #[serde(default)]
+    pub output: OutputConfig,
+
+    #[serde(default)]
+    pub retry: RetryConfig,
+
+    #[serde(default)]
+    pub client: ClientConfig,
+
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+
+    #[serde(default)]
+    pub storage: StorageConfig,
+
+    #[serde(default)]
+    pub selectors: SelectorConfig,
+
+    #[serde(default)]
+    pub scraper: ScraperConfig,
+
+    #[serde(default)]
+    pub processing: ProcessingConfig,
+
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+
+    #[serde(default)]
+    pub health: HealthConfig,
+}
+
+pub const SUPPORTED_PROXY_SCHEMES: [&str; 5] = ["http", "https", "socks4", "socks5", "socks5h"];
+
+pub const ENV_OVERRIDES: [&str; 8] = [
+    "CLUTCH_BASE_URL",
+    "CLUTCH_PROXY_FILE",
+    "CLUTCH_START_PATH",
+    "CLUTCH_MAX_RETRIES",
+    "CLUTCH_RETRY_DELAY",
+    "CLUTCH_CONCURRENCY",
+    "CLUTCH_RESUME",
+    "CLUTCH_LOG_LEVEL",
+];
+
+fn env_override(key: &str) -> Option<String> {
+    std::env::var(key)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+}
+
+fn parse_env_override<T>(key: &str, value: &str) -> Result<T>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    value.trim().parse().map_err(|e| {
+        ConfigError::InvalidValue(format!(
+            "{}={:?} could not be parsed ({}); supported overrides: {}",
+            key,
+            value,
+            e,
+            ENV_OVERRIDES.join(", ")
+        ))
+        .into()
+    })
 }
 
 // Default implementations
@@ -68,20 +475,195 @@ impl Default for ProxyConfig {
     fn default() -> Self {
         Self {
             file: default_proxy_file(),
+            scheme: default_proxy_scheme(),
+            single: None,
             switch_delay: default_proxy_switch_delay(),
             max_retries: default_proxy_max_retries(),
             request_timeout: default_proxy_request_timeout(),
             concurrent_validations: default_proxy_concurrent_validations(),
+            stats_file: None,
+            blacklist_file: None,
+            report_file: None,
+            revalidate: false,
+            resurrection_interval: 0,
+            adaptive_timeout: false,
+            timeout_multiplier: default_proxy_timeout_multiplier(),
+            min_timeout: default_proxy_min_timeout(),
+            max_timeout: default_proxy_max_timeout(),
+            allowed_countries: Vec::new(),
+            geo_lookup_url: default_proxy_geo_lookup_url(),
+            selection: ProxySelectionConfig::default(),
+            progress_interval: default_proxy_progress_interval(),
+            max_concurrent_per_proxy: default_proxy_max_concurrent_per_proxy(),
+            warmup: false,
+            autodetect_scheme: false,
         }
     }
 }
 
+impl Default for ProxySelectionConfig {
+    fn default() -> Self {
+        Self {
+            failure_weight: default_selection_failure_weight(),
+            latency_weight: default_selection_latency_weight(),
+            success_weight: default_selection_success_weight(),
+            jitter: default_selection_jitter(),
+            min_requests: default_selection_min_requests(),
+        }
+    }
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            base_delay: None,
+            max_delay: default_retry_max_delay(),
+            transient_retries: default_retry_transient_retries(),
+            server_error_retries: default_retry_server_error_retries(),
+            window_secs: default_retry_window_secs(),
+        }
+    }
+}
+
+impl Default for ScraperConfig {
+    fn default() -> Self {
+        Self {
+            min_success_ratio: 0.0,
+            min_content_length: default_scraper_min_content_length(),
+        }
+    }
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_metrics_port(),
+        }
+    }
+}
+
+impl Default for SelectorConfig {
+    fn default() -> Self {
+        Self {
+            providers_list: default_providers_list_selectors(),
+            review_container: default_review_container_selector(),
+            review_quote: default_review_quote_selector(),
+            review_reviewer: default_review_reviewer_selector(),
+            review_role: default_review_role_selector(),
+            awards: default_awards_selector(),
+            verified_badge: default_verified_badge_selector(),
+            sponsored_classes: default_sponsored_classes(),
+        }
+    }
+}
+
+impl SelectorConfig {
+    fn all(&self) -> [(&'static str, &str); 6] {
+        [
+            ("review_container", &self.review_container),
+            ("review_quote", &self.review_quote),
+            ("review_reviewer", &self.review_reviewer),
+            ("review_role", &self.review_role),
+            ("awards", &self.awards),
+            ("verified_badge", &self.verified_badge),
+        ]
+    }
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            compress: false,
+            html_dir: default_storage_html_dir(),
+            json_dir: default_storage_json_dir(),
+            checkpoint_file: None,
+        }
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: 0,
+            burst: default_rate_limit_burst(),
+        }
+    }
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            impersonate: default_client_impersonate(),
+            user_agents: Vec::new(),
+            trace_body_chars: default_trace_body_chars(),
+            accept_language: default_client_accept_language(),
+            max_redirects: default_client_max_redirects(),
+        }
+    }
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            format: default_output_format(),
+            formats: Vec::new(),
+            mode: default_output_mode(),
+            json_file: default_output_json_file(),
+            csv_file: default_output_csv_file(),
+            jsonl_file: default_output_jsonl_file(),
+            redact_fields: Vec::new(),
+            redact_mode: default_output_redact_mode(),
+            previous_file: None,
+            changes_file: default_output_changes_file(),
+            sink: default_output_sink(),
+            webhook_url: None,
+            webhook_batch_size: default_output_webhook_batch_size(),
+            webhook_retries: default_output_webhook_retries(),
+        }
+    }
+}
+
+impl OutputConfig {
+    // `formats` wins over the single `format`; `both` expands to JSON and CSV
+    pub fn formats(&self) -> Vec<OutputFormat> {
+        let requested = if self.formats.is_empty() {
+            std::slice::from_ref(&self.format)
+        } else {
+            self.formats.as_slice()
+        };
+
+        let mut formats = Vec::new();
+        for format in requested {
+            let expanded: &[OutputFormat] = match format {
+                OutputFormat::Both => &[OutputFormat::Json, OutputFormat::Csv],
+                other => std::slice::from_ref(other),
+            };
+            for format in expanded {
+                if !formats.contains(format) {
+                    formats.push(*format);
+                }
+            }
+        }
+        formats
+    }
+
+    pub fn writes(&self, format: OutputFormat) -> bool {
+        self.formats().contains(&format)
+    }
+}
+
 impl Default for LogConfig {
     fn default() -> Self {
         Self {
             level: default_log_level(),
             directory: default_log_directory(),
             filename: default_log_filename(),
+            error_filename: None,
+            format: LogFormat::default(),
+            max_log_size_mb: 0,
+            max_log_files: default_max_log_files(),
+            stdout: default_log_stdout(),
         }
     }
 }
@@ -90,14 +672,84 @@ impl Config {
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let content = std::fs::read_to_string(path).map_err(ConfigError::FileRead)?;
 
-        let config: Config = toml::from_str(&content).map_err(ConfigError::Parse)?;
+        let mut config: Config = toml::from_str(&content).map_err(ConfigError::Parse)?;
+        config.apply_env_overrides()?;
 
         config.validate()?;
         info!("Configuration loaded successfully");
         Ok(config)
     }
 
-    fn validate(&self) -> Result<()> {
+    // Lets CI override file values through `CLUTCH_*` environment variables
+    pub fn apply_env_overrides(&mut self) -> Result<()> {
+        if let Some(value) = env_override("CLUTCH_BASE_URL") {
+            self.base_url = value;
+        }
+        if let Some(value) = env_override("CLUTCH_PROXY_FILE") {
+            self.proxy_file = value.clone();
+            self.proxy.file = value;
+        }
+        if let Some(value) = env_override("CLUTCH_START_PATH") {
+            self.start_path = value;
+        }
+        if let Some(value) = env_override("CLUTCH_MAX_RETRIES") {
+            self.max_retries = parse_env_override("CLUTCH_MAX_RETRIES", &value)?;
+        }
+        if let Some(value) = env_override("CLUTCH_RETRY_DELAY") {
+            self.retry_delay = parse_env_override("CLUTCH_RETRY_DELAY", &value)?;
+        }
+        if let Some(value) = env_override("CLUTCH_CONCURRENCY") {
+            self.concurrency = parse_env_override("CLUTCH_CONCURRENCY", &value)?;
+        }
+        if let Some(value) = env_override("CLUTCH_RESUME") {
+            self.resume = parse_env_override("CLUTCH_RESUME", &value)?;
+        }
+        if let Some(value) = env_override("CLUTCH_LOG_LEVEL") {
+            self.logging.level = value;
+        }
+        Ok(())
+    }
+
+    pub fn retry_base_delay(&self) -> u64 {
+        self.retry.base_delay.unwrap_or(self.proxy.switch_delay)
+    }
+
+    // Copy of this config scraping `start_path`, with pages, checkpoint and
+    // output files moved into a subdirectory named after the category
+    pub fn for_category(&self, start_path: &str) -> Config {
+        let slug = utils::category_slug(start_path);
+        let in_dir = |dir: &str| Path::new(dir).join(&slug).to_string_lossy().into_owned();
+        let in_parent = |file: &str| {
+            let file = Path::new(file);
+            file.parent()
+                .unwrap_or(Path::new(""))
+                .join(&slug)
+                .join(file.file_name().unwrap_or_default())
+                .to_string_lossy()
+                .into_owned()
+        };
+
+        let mut config = self.clone();
+        config.start_path = start_path.to_string();
+        config.storage.html_dir = in_dir(&self.storage.html_dir);
+        config.storage.json_dir = in_dir(&self.storage.json_dir);
+        config.storage.checkpoint_file = self.storage.checkpoint_file.as_deref().map(in_parent);
+        config.output.json_file = in_parent(&self.output.json_file);
+        config.output.csv_file = in_parent(&self.output.csv_file);
+        config.output.jsonl_file = in_parent(&self.output.jsonl_file);
+        config.output.previous_file = self.output.previous_file.as_deref().map(in_parent);
+        config.output.changes_file = in_parent(&self.output.changes_file);
+        config
+    }
+
+    pub fn request_delay(&self) -> Duration {
+        utils::random_delay(
+            Duration::from_secs(self.request_delay_min),
+            Duration::from_secs(self.request_delay_max),
+        )
+    }
+
+    pub fn validate(&self) -> Result<()> {
         // Validate base_url
         if self.base_url.is_empty() {
             return Err(ConfigError::MissingField("base_url".to_string()).into());
@@ -110,11 +762,81 @@ impl Config {
             .into());
         }
 
-        // Validate proxy_file if provided
-        if !self.proxy_file.is_empty() && !Path::new(&self.proxy_file).exists() {
+        // Validate proxy.file when it is a local path; URLs are downloaded at startup
+        let proxy_file = &self.proxy.file;
+        let remote_proxy_file =
+            proxy_file.starts_with("http://") || proxy_file.starts_with("https://");
+        if self.proxy.single.is_none()
+            && !proxy_file.is_empty()
+            && !remote_proxy_file
+            && !Path::new(proxy_file).exists()
+        {
+            return Err(ConfigError::InvalidValue(format!(
+                "proxy.file does not exist: {}",
+                proxy_file
+            ))
+            .into());
+        }
+
+        if let Some(single) = &self.proxy.single {
+            let scheme = Url::parse(single)
+                .map(|url| url.scheme().to_string())
+                .map_err(|e| {
+                    ConfigError::InvalidValue(format!(
+                        "proxy.single is not a valid proxy URL ({}): {}",
+                        single, e
+                    ))
+                })?;
+            if !SUPPORTED_PROXY_SCHEMES.contains(&scheme.as_str()) {
+                return Err(ConfigError::InvalidValue(format!(
+                    "proxy.single has an unsupported scheme {} (expected one of: {})",
+                    scheme,
+                    SUPPORTED_PROXY_SCHEMES.join(", ")
+                ))
+                .into());
+            }
+        }
+
+        if let Some(categories_file) = &self.categories_file {
+            if !Path::new(categories_file).exists() {
+                return Err(ConfigError::InvalidValue(format!(
+                    "categories_file does not exist: {}",
+                    categories_file
+                ))
+                .into());
+            }
+        }
+
+        if !SUPPORTED_PROXY_SCHEMES.contains(&self.proxy.scheme.as_str()) {
+            return Err(ConfigError::InvalidValue(format!(
+                "proxy.scheme must be one of {}: {}",
+                SUPPORTED_PROXY_SCHEMES.join(", "),
+                self.proxy.scheme
+            ))
+            .into());
+        }
+
+        if self.proxy.adaptive_timeout {
+            if self.proxy.timeout_multiplier <= 0.0 {
+                return Err(ConfigError::InvalidValue(
+                    "proxy.timeout_multiplier must be greater than 0".to_string(),
+                )
+                .into());
+            }
+            if self.proxy.min_timeout == 0 || self.proxy.min_timeout > self.proxy.max_timeout {
+                return Err(ConfigError::InvalidValue(format!(
+                    "proxy.min_timeout must be between 1 and proxy.max_timeout ({}): {}",
+                    self.proxy.max_timeout, self.proxy.min_timeout
+                ))
+                .into());
+            }
+        }
+
+        let base_delay = self.retry_base_delay();
+        if base_delay == 0 || base_delay > self.retry.max_delay {
             return Err(ConfigError::InvalidValue(format!(
-                "proxy_file does not exist: {}",
-                self.proxy_file
+                "retry.base_delay must be between 1 and retry.max_delay ({}): {}",
+                self.retry.max_delay, base_delay
             ))
             .into());
         }
@@ -140,6 +862,130 @@ impl Config {
             .into());
         }
 
+        if self.start_page == 0 {
+            return Err(
+                ConfigError::InvalidValue("start_page must be 1 or greater".to_string()).into(),
+            );
+        }
+
+        if self.run_deadline_secs == Some(0) {
+            return Err(ConfigError::InvalidValue(
+                "run_deadline_secs must be greater than 0 when set".to_string(),
+            )
+            .into());
+        }
+
+        if self.max_pages == Some(0) {
+            return Err(ConfigError::InvalidValue(
+                "max_pages must be greater than 0 when set".to_string(),
+            )
+            .into());
+        }
+
+        if self.request_delay_min > self.request_delay_max {
+            return Err(ConfigError::InvalidValue(format!(
+                "request_delay_min ({}) must not exceed request_delay_max ({})",
+                self.request_delay_min, self.request_delay_max
+            ))
+            .into());
+        }
+
+        if self.max_companies == Some(0) {
+            return Err(ConfigError::InvalidValue(
+                "max_companies must be greater than 0 when set".to_string(),
+            )
+            .into());
+        }
+
+        if self.concurrency == 0 {
+            return Err(ConfigError::InvalidValue(
+                "concurrency must be greater than 0".to_string(),
+            )
+            .into());
+        }
+
+        if self.storage.html_dir.trim().is_empty() || self.storage.json_dir.trim().is_empty() {
+            return Err(ConfigError::InvalidValue(
+                "storage.html_dir and storage.json_dir must not be empty".to_string(),
+            )
+            .into());
+        }
+
+        if self.selectors.providers_list.is_empty() {
+            return Err(ConfigError::InvalidValue(
+                "selectors.providers_list needs at least one selector".to_string(),
+            )
+            .into());
+        }
+        for selector in &self.selectors.providers_list {
+            scraper::Selector::parse(selector).map_err(|e| {
+                ConfigError::InvalidValue(format!(
+                    "selectors.providers_list is not a valid CSS selector ({}): {}",
+                    selector, e
+                ))
+            })?;
+        }
+
+        for (name, selector) in self.selectors.all() {
+            scraper::Selector::parse(selector).map_err(|e| {
+                ConfigError::InvalidValue(format!(
+                    "selectors.{} is not a valid CSS selector ({}): {}",
+                    name, selector, e
+                ))
+            })?;
+        }
+
+        let selection = &self.proxy.selection;
+        if [
+            selection.failure_weight,
+            selection.latency_weight,
+            selection.success_weight,
+            selection.jitter,
+        ]
+        .iter()
+        .any(|weight| !weight.is_finite() || *weight < 0.0)
+        {
+            return Err(ConfigError::InvalidValue(
+                "proxy.selection weights and jitter must be non-negative numbers".to_string(),
+            )
+            .into());
+        }
+
+        if let Some(country) =
+            self.proxy.allowed_countries.iter().find(|country| {
+                country.len() != 2 || !country.chars().all(|c| c.is_ascii_alphabetic())
+            })
+        {
+            return Err(ConfigError::InvalidValue(format!(
+                "proxy.allowed_countries must hold two-letter country codes: {}",
+                country
+            ))
+            .into());
+        }
+
+        if self.rate_limit.burst == 0 {
+            return Err(ConfigError::InvalidValue(
+                "rate_limit.burst must be greater than 0".to_string(),
+            )
+            .into());
+        }
+
+        if !(0.0..=1.0).contains(&self.scraper.min_success_ratio) {
+            return Err(ConfigError::InvalidValue(format!(
+                "scraper.min_success_ratio must be between 0.0 and 1.0: {}",
+                self.scraper.min_success_ratio
+            ))
+            .into());
+        }
+
+        if !(0.0..=1.0).contains(&self.underfill_threshold) {
+            return Err(ConfigError::InvalidValue(format!(
+                "underfill_threshold must be between 0 and 1: {}",
+                self.underfill_threshold
+            ))
+            .into());
+        }
+
         if self.start_path.is_empty() {
             return Err(ConfigError::InvalidValue("start_path cannot be empty".to_string()).into());
         }
@@ -165,6 +1011,79 @@ impl Config {
             .into());
         }
 
+        if self.output.writes(OutputFormat::Json)
+            && self.output.mode == OutputMode::Aggregate
+            && self.output.json_file.is_empty()
+        {
+            return Err(ConfigError::InvalidValue(
+                "output.json_file cannot be empty in aggregate mode".to_string(),
+            )
+            .into());
+        }
+
+        if self.output.writes(OutputFormat::Csv) && self.output.csv_file.is_empty() {
+            return Err(ConfigError::InvalidValue(
+                "output.csv_file cannot be empty when writing CSV".to_string(),
+            )
+            .into());
+        }
+
+        if self.output.writes(OutputFormat::Jsonl) && self.output.jsonl_file.is_empty() {
+            return Err(ConfigError::InvalidValue(
+                "output.jsonl_file cannot be empty when writing JSON Lines".to_string(),
+            )
+            .into());
+        }
+
+        if let Some(previous_file) = &self.output.previous_file {
+            let written_files = [
+                &self.output.json_file,
+                &self.output.jsonl_file,
+                &self.output.changes_file,
+            ];
+            if written_files.contains(&previous_file) {
+                return Err(ConfigError::InvalidValue(format!(
+                    "output.previous_file must differ from the output files, or the next run diffs against a partial set: {}",
+                    previous_file
+                ))
+                .into());
+            }
+        }
+
+        if self.output.sink == OutputSinkKind::Webhook {
+            let Some(webhook_url) = &self.output.webhook_url else {
+                return Err(ConfigError::MissingField(
+                    "output.webhook_url is required when output.sink is webhook".to_string(),
+                )
+                .into());
+            };
+            Url::parse(webhook_url).map_err(|e| {
+                ConfigError::InvalidValue(format!(
+                    "output.webhook_url is not a valid URL: {} ({})",
+                    webhook_url, e
+                ))
+            })?;
+            if self.output.webhook_batch_size == 0 {
+                return Err(ConfigError::InvalidValue(
+                    "output.webhook_batch_size must be greater than 0".to_string(),
+                )
+                .into());
+            }
+        }
+
+        for field in &self.output.redact_fields {
+            if !CompanyData::REDACTABLE_FIELDS.contains(&field.as_str()) {
+                return Err(ConfigError::InvalidValue(format!(
+                    "output.redact_fields contains unknown field: {} (expected one of: {})",
+                    field,
+                    CompanyData::REDACTABLE_FIELDS.join(", ")
+                ))
+                .into());
+            }
+        }
+
+        crate::client::parse_impersonation(&self.client.impersonate)?;
+
         Ok(())
     }
 }
@@ -177,6 +1096,54 @@ fn default_proxy_file() -> String {
     "proxy.txt".to_string()
 }
 
+fn default_proxy_scheme() -> String {
+    "socks5".to_string()
+}
+
+fn default_selection_failure_weight() -> f64 {
+    1.0
+}
+
+fn default_selection_latency_weight() -> f64 {
+    1.0
+}
+
+fn default_selection_success_weight() -> f64 {
+    2.0
+}
+
+fn default_selection_jitter() -> f64 {
+    0.5
+}
+
+fn default_selection_min_requests() -> usize {
+    3
+}
+
+fn default_proxy_progress_interval() -> usize {
+    25
+}
+
+fn default_proxy_max_concurrent_per_proxy() -> usize {
+    1
+}
+
+fn default_proxy_geo_lookup_url() -> String {
+    "https://ipinfo.io/country".to_string()
+}
+
+fn default_log_stdout() -> bool {
+    true
+}
+
+fn default_dedupe() -> bool {
+    true
+}
+
+fn default_preflight() -> bool {
+    true
+}
+
 fn default_max_retries() -> u32 {
     3
 }
@@ -185,6 +1152,10 @@ fn default_retry_delay() -> u64 {
     5
 }
 
+fn default_retry_max_delay() -> u64 {
+    60
+}
+
 fn default_proxy_switch_delay() -> u64 {
     2
 }
@@ -193,6 +1164,22 @@ fn default_start_path() -> String {
     "/developers/rust".to_string()
 }
 
+fn default_start_page() -> usize {
+    1
+}
+
+fn default_request_delay() -> u64 {
+    2
+}
+
+fn default_concurrency() -> usize {
+    1
+}
+
+fn default_underfill_threshold() -> f64 {
+    1.0
+}
+
 fn default_proxy_max_retries() -> u32 {
     2
 }
@@ -201,6 +1188,18 @@ fn default_proxy_request_timeout() -> u64 {
     15
 }
 
+fn default_proxy_timeout_multiplier() -> f64 {
+    3.0
+}
+
+fn default_proxy_min_timeout() -> u64 {
+    5
+}
+
+fn default_proxy_max_timeout() -> u64 {
+    60
+}
+
 fn default_proxy_concurrent_validations() -> usize {
     5
 }
@@ -216,3 +1215,151 @@ fn default_log_directory() -> String {
 fn default_log_filename() -> String {
     "scraper.log".to_string()
 }
+
+fn default_max_log_files() -> usize {
+    5
+}
+
+fn default_review_container_selector() -> String {
+    ".provider__testimonial, .provider-review-highlight".to_string()
+}
+
+fn default_review_quote_selector() -> String {
+    "blockquote, .provider__testimonial-text".to_string()
+}
+
+fn default_review_reviewer_selector() -> String {
+    ".provider__testimonial-author, .reviewer_name".to_string()
+}
+
+fn default_review_role_selector() -> String {
+    ".provider__testimonial-position, .reviewer_position".to_string()
+}
+
+fn default_retry_window_secs() -> u64 {
+    60
+}
+
+fn default_scraper_min_content_length() -> usize {
+    1024
+}
+
+fn default_metrics_port() -> u16 {
+    9898
+}
+
+fn default_providers_list_selectors() -> Vec<String> {
+    vec![
+        "ul.providers__list#providers__list".to_string(),
+        "ul.providers__list".to_string(),
+    ]
+}
+
+fn default_awards_selector() -> String {
+    ".provider__awards-item, .provider-award, .sg-award-badge".to_string()
+}
+
+fn default_verified_badge_selector() -> String {
+    ".provider__verified-badge, .verified-badge".to_string()
+}
+
+fn default_sponsored_classes() -> Vec<String> {
+    vec![
+        "provider-list-item--sponsored".to_string(),
+        "provider-list-item--featured".to_string(),
+        "sponsor".to_string(),
+    ]
+}
+
+fn default_retry_server_error_retries() -> u32 {
+    2
+}
+
+fn default_retry_transient_retries() -> u32 {
+    1
+}
+
+fn default_storage_html_dir() -> String {
+    "local_html".to_string()
+}
+
+fn default_storage_json_dir() -> String {
+    "json_data".to_string()
+}
+
+fn default_rate_limit_burst() -> u32 {
+    1
+}
+
+fn default_client_impersonate() -> String {
+    "chrome131".to_string()
+}
+
+fn default_client_accept_language() -> String {
+    crate::client::DEFAULT_ACCEPT_LANGUAGE.to_string()
+}
+
+fn default_trace_body_chars() -> usize {
+    500
+}
+
+// Enough for Clutch's canonical redirects (trailing slash, `?page=1`); where a
+// redirect lands is still checked against the requested page
+fn default_client_max_redirects() -> usize {
+    5
+}
+
+fn default_output_format() -> OutputFormat {
+    OutputFormat::Json
+}
+
+fn default_output_mode() -> OutputMode {
+    OutputMode::PerCompany
+}
+
+fn default_output_json_file() -> String {
+    "json_data/companies.json".to_string()
+}
+
+fn default_output_csv_file() -> String {
+    "csv_data/companies.csv".to_string()
+}
+
+fn default_output_jsonl_file() -> String {
+    "json_data/companies.jsonl".to_string()
+}
+
+fn default_output_redact_mode() -> RedactMode {
+    RedactMode::Hash
+}
+
+fn default_output_changes_file() -> String {
+    "json_data/changes.json".to_string()
+}
+
+fn default_output_sink() -> OutputSinkKind {
+    OutputSinkKind::File
+}
+
+fn default_output_webhook_batch_size() -> usize {
+    50
+}
+
+fn default_output_webhook_retries() -> u32 {
+    3
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Config;
+
+    #[test]
+    fn remote_proxy_list_needs_no_local_file() {
+        let config: Config =
+            toml::from_str("[proxy]\nfile = \"https://example.com/proxies.txt\"").unwrap();
+        assert!(config.validate().is_ok());
+
+        let config: Config = toml::from_str("[proxy]\nfile = \"missing-proxies.txt\"").unwrap();
+        assert!(config.validate().is_err());
+    }
+}
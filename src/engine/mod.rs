@@ -0,0 +1,1274 @@
+#[cfg(test)]
+mod tests;
+
+use crate::client::{self, Client, RedirectPolicy};
+use crate::config::{Config, OutputSinkKind};
+use crate::error::{AppError, ClientError, Result, ScraperError};
+use crate::metrics::{self, Metrics};
+use crate::output::{self, FileSink, PageCompanies, ScrapeMetadata, WebhookSink};
+use crate::proxy::{ProxyManager, ProxyStats, ProxyStatsSnapshot};
+use crate::retry::{CircuitBreaker, RetryBudget};
+use crate::scraper::{page_number_of, page_path, ClutchAdapter, PageInfo, Scraper};
+use crate::utils::{self, Checkpoint, RunSummary};
+use crate::{log_error, log_info, log_warn};
+use futures::future::{BoxFuture, FutureExt, Shared};
+use futures::{stream::FuturesUnordered, StreamExt};
+use http::header::{self, HeaderMap, HeaderValue};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Resolves when the caller asks the run to stop downloading early
+type Shutdown = Shared<BoxFuture<'static, ()>>;
+
+// Runs a whole scrape with `config`: validates the proxies, downloads the
+// listing pages and hands the extracted companies to the configured output.
+// Setting up logging is left to the caller. Fails with `InsufficientCompanies`
+// when fewer than `min_companies_for_success` companies came out. Outside of
+// dry runs the summary file is written either way, with `success: false` when
+// any step failed.
+pub async fn run(config: Config) -> Result<RunSummary> {
+    run_until(config, std::future::pending()).await
+}
+
+// Like `run`, but stops downloading once `shutdown` resolves (e.g. on Ctrl+C)
+// and still processes the pages saved so far. Signals are left to the caller.
+pub async fn run_until(
+    config: Config,
+    shutdown: impl std::future::Future<Output = ()> + Send + 'static,
+) -> Result<RunSummary> {
+    let started_at = utils::now_rfc3339();
+    let mut progress = RunProgress::default();
+    let result = scrape(&config, shutdown.boxed().shared(), &mut progress).await;
+
+    let summary = progress.summary(started_at, result.is_ok()).await;
+    if let (Some(path), false) = (&config.summary_file, config.dry_run) {
+        write_run_summary(path, &summary);
+    }
+    result.map(|_| summary)
+}
+
+// The body of `run_until`, recording what it got through in `progress` so a
+// summary can be written however it returns
+async fn scrape(config: &Config, shutdown: Shutdown, progress: &mut RunProgress) -> Result<()> {
+    let deadline = config
+        .run_deadline_secs
+        .map(|secs| tokio::time::Instant::now() + Duration::from_secs(secs));
+
+    // Initialize proxy manager
+    log_info!("[main] Initializing proxy manager...");
+    let proxy_manager = match &config.proxy.single {
+        Some(proxy) => ProxyManager::single(proxy, config.clone()).await?,
+        None => ProxyManager::new(&config.proxy.file, config.clone()).await?,
+    };
+    progress.proxy_manager = Some(proxy_manager.clone());
+
+    if config.dry_run {
+        proxy_manager.print_report().await;
+        log_info!(
+            "[main] Dry run complete: configuration is valid, {} working / {} dead proxies (validated this run: {})",
+            proxy_manager.working_count().await,
+            proxy_manager.dead_count().await,
+            proxy_manager.validation_summary()
+        );
+        return Ok(());
+    }
+
+    // Stopped when the run returns, so a later run can bind the port again
+    let _metrics_server = (config.metrics.enabled || config.health.enabled).then(|| {
+        AbortOnDrop(metrics::spawn_server(
+            config.metrics.port,
+            metrics::Endpoints {
+                metrics: config.metrics.enabled,
+                health: config.health.enabled,
+            },
+            proxy_manager.clone(),
+        ))
+    });
+
+    if config.proxy.warmup {
+        proxy_manager.warm_up().await;
+    }
+
+    if config.preflight {
+        preflight(config, &proxy_manager).await?;
+    }
+    let resurrection = proxy_manager.spawn_resurrection().map(AbortOnDrop);
+
+    let categories = match &config.categories_file {
+        Some(path) => utils::read_category_paths(Path::new(path))?,
+        None => Vec::new(),
+    };
+
+    // First phase: Download and save all pages
+    log_info!("[main] Starting download phase...");
+
+    wait_initial_delay(config).await;
+
+    let processed = if categories.is_empty() {
+        download_until_interrupted(config, &proxy_manager, deadline, &shutdown).await?;
+        finish_downloads(config, &proxy_manager, resurrection).await;
+        process_pages(config, proxy_manager.metrics()).await?
+    } else {
+        let processed =
+            scrape_categories(config, &proxy_manager, &categories, deadline, &shutdown).await?;
+        finish_downloads(config, &proxy_manager, resurrection).await;
+        processed
+    };
+
+    progress.processed = processed;
+    check_min_companies(processed.companies, config.min_companies_for_success)
+}
+
+// Holds off the first request for `initial_delay_secs`
+async fn wait_initial_delay(config: &Config) {
+    if config.initial_delay_secs == 0 {
+        return;
+    }
+    log_info!(
+        "[main] Waiting {} seconds before the first request...",
+        config.initial_delay_secs
+    );
+    tokio::time::sleep(Duration::from_secs(config.initial_delay_secs)).await;
+}
+
+// Aborts a background task when dropped, including on early error returns
+struct AbortOnDrop(tokio::task::JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+// Pages and companies produced by the processing phase
+#[derive(Debug, Default, Clone, Copy)]
+struct ProcessingSummary {
+    pages: usize,
+    companies: usize,
+    duplicates: usize,
+}
+
+// How far a run got, which is all there is to report when it fails early
+#[derive(Default)]
+struct RunProgress {
+    proxy_manager: Option<ProxyManager>,
+    processed: ProcessingSummary,
+}
+
+impl RunProgress {
+    async fn summary(&self, started_at: String, success: bool) -> RunSummary {
+        let (pages_downloaded, working_proxies, dead_proxies) = match &self.proxy_manager {
+            Some(proxy_manager) => (
+                proxy_manager.metrics().pages_fetched(),
+                proxy_manager.working_count().await,
+                proxy_manager.dead_count().await,
+            ),
+            None => (0, 0, 0),
+        };
+        RunSummary {
+            started_at,
+            finished_at: utils::now_rfc3339(),
+            success,
+            pages_downloaded,
+            files_processed: self.processed.pages,
+            companies_extracted: self.processed.companies,
+            duplicates_dropped: self.processed.duplicates,
+            working_proxies,
+            dead_proxies,
+        }
+    }
+}
+
+fn write_run_summary(path: &str, summary: &RunSummary) {
+    match utils::save_json(summary, path) {
+        Ok(()) => {
+            log_info!("[main] Wrote run summary to {}", path);
+        }
+        Err(e) => {
+            log_error!("[main] Failed to write run summary to {}: {}", path, e);
+        }
+    }
+}
+
+// Runs the download phase until it finishes, `shutdown` resolves or the run
+// deadline passes. Returns true when stopped early.
+async fn download_until_interrupted(
+    config: &Config,
+    proxy_manager: &ProxyManager,
+    deadline: Option<tokio::time::Instant>,
+    shutdown: &Shutdown,
+) -> Result<bool> {
+    if past_deadline(deadline) {
+        log_warn!("[main] Run deadline already passed, skipping the download");
+        return Ok(true);
+    }
+
+    tokio::select! {
+        result = download_pages(config, proxy_manager, deadline) => {
+            result.map(|_| past_deadline(deadline))
+        }
+        _ = shutdown.clone() => {
+            log_warn!("[main] Graceful shutdown requested. Stopping downloads and processing saved pages.");
+            Ok(true)
+        }
+        _ = sleep_until_deadline(deadline) => {
+            log_warn!(
+                "[main] Run deadline reached after {} pages, stopping downloads and processing saved pages",
+                proxy_manager.metrics().pages_fetched()
+            );
+            Ok(true)
+        }
+    }
+}
+
+fn past_deadline(deadline: Option<tokio::time::Instant>) -> bool {
+    deadline.is_some_and(|deadline| tokio::time::Instant::now() >= deadline)
+}
+
+async fn sleep_until_deadline(deadline: Option<tokio::time::Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn finish_downloads(
+    config: &Config,
+    proxy_manager: &ProxyManager,
+    resurrection: Option<AbortOnDrop>,
+) {
+    drop(resurrection);
+
+    // Print proxy performance report
+    log_info!("\n[main] Download phase completed. Generating proxy report...");
+    proxy_manager.print_report().await;
+    if let Some(report_file) = &config.proxy.report_file {
+        if let Err(e) = proxy_manager
+            .write_report_json(std::path::Path::new(report_file))
+            .await
+        {
+            log_error!("[main] Failed to write proxy report: {}", e);
+        }
+    }
+    if let Err(e) = proxy_manager.save_stats().await {
+        log_error!("[main] Failed to save proxy statistics: {}", e);
+    }
+}
+
+// Scrapes each category path in turn with the shared proxy pool, writing each
+// one's pages and output under its own directories. A failed category is
+// logged and skipped. Returns the totals across all categories; the proxy
+// stats are reset between categories and summed back up before returning.
+async fn scrape_categories(
+    config: &Config,
+    proxy_manager: &ProxyManager,
+    categories: &[String],
+    deadline: Option<tokio::time::Instant>,
+    shutdown: &Shutdown,
+) -> Result<ProcessingSummary> {
+    let mut results = Vec::new();
+    let mut proxy_totals: HashMap<String, ProxyStats> = HashMap::new();
+    for (index, start_path) in categories.iter().enumerate() {
+        log_info!(
+            "[main] Category {}/{}: {}",
+            index + 1,
+            categories.len(),
+            start_path
+        );
+        let category_config = config.for_category(start_path);
+
+        let downloaded =
+            download_until_interrupted(&category_config, proxy_manager, deadline, shutdown).await;
+        let snapshot = proxy_manager.snapshot_and_reset_stats().await;
+        log_category_proxy_report(start_path, &snapshot);
+        for (url, proxy) in snapshot {
+            proxy_totals.entry(url).or_default().merge(&proxy.stats);
+        }
+
+        let interrupted = match downloaded {
+            Ok(interrupted) => interrupted,
+            // The site is blocking everything, later categories would fail too
+            Err(e @ AppError::Scraper(ScraperError::ConsecutiveFailures { .. })) => {
+                proxy_manager.prepend_stats(proxy_totals).await;
+                return Err(e);
+            }
+            Err(e) => {
+                log_error!("[main] Skipping category {}: {}", start_path, e);
+                results.push((start_path, Err(e)));
+                continue;
+            }
+        };
+        let result = process_pages(&category_config, proxy_manager.metrics()).await;
+        if let Err(e) = &result {
+            log_error!("[main] Skipping category {}: {}", start_path, e);
+        }
+        results.push((start_path, result));
+
+        if interrupted {
+            log_warn!(
+                "[main] Skipping {} remaining categories, the run was stopped early",
+                categories.len() - index - 1
+            );
+            break;
+        }
+    }
+    proxy_manager.prepend_stats(proxy_totals).await;
+
+    log_info!("[main] Category summary:");
+    let mut total = ProcessingSummary::default();
+    for (start_path, result) in &results {
+        match result {
+            Ok(processed) => {
+                total.pages += processed.pages;
+                total.companies += processed.companies;
+                total.duplicates += processed.duplicates;
+                log_info!(
+                    "[main]   {}: {} pages, {} companies",
+                    start_path,
+                    processed.pages,
+                    processed.companies
+                );
+            }
+            Err(e) => {
+                log_info!("[main]   {}: failed ({})", start_path, e);
+            }
+        }
+    }
+    Ok(total)
+}
+
+// Proxy usage while downloading one category. The counters are reset after
+// each category and restored by `scrape_categories`, so the end-of-run report
+// still covers the whole run.
+fn log_category_proxy_report(start_path: &str, snapshot: &HashMap<String, ProxyStatsSnapshot>) {
+    let mut used: Vec<_> = snapshot
+        .iter()
+        .filter(|(_, proxy)| proxy.stats.total_requests > 0)
+        .collect();
+    used.sort_by(|a, b| a.0.cmp(b.0));
+
+    log_info!(
+        "[main] Proxy usage for {}: {} proxies sent requests",
+        start_path,
+        used.len()
+    );
+    for (url, proxy) in used {
+        log_info!(
+            "[main]   {}{}: {} requests, {:.1}% success, average latency {}",
+            url,
+            if proxy.active { "" } else { " (dead)" },
+            proxy.stats.total_requests,
+            proxy.stats.success_rate() * 100.0,
+            proxy
+                .stats
+                .average_latency()
+                .map_or("n/a".to_string(), |latency| format!("{:.2?}", latency))
+        );
+    }
+}
+
+// Second phase: extracts companies from the saved pages and writes the outputs
+async fn process_pages(config: &Config, metrics: &Metrics) -> Result<ProcessingSummary> {
+    let base_url = &config.base_url;
+
+    // Check if we have any files to process
+    let html_dir = std::path::Path::new(&config.storage.html_dir);
+    if !html_dir.exists() || html_dir.read_dir()?.next().is_none() {
+        log_error!(
+            "[main] No HTML files found in {} directory. Skipping processing phase.",
+            config.storage.html_dir
+        );
+        return Ok(ProcessingSummary::default());
+    }
+
+    // Second phase: Process saved files
+    log_info!("\n[main] Starting processing phase...");
+    let saved_files = utils::read_html_files(&config.storage.html_dir)?;
+
+    if saved_files.is_empty() {
+        log_error!("[main] No HTML files found to process.");
+        return Ok(ProcessingSummary::default());
+    }
+
+    let adapter = ClutchAdapter::new(&config.selectors);
+
+    // Parsing is CPU-bound, so spread it over a thread pool. `collect` on an
+    // indexed iterator keeps the results in page order.
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(config.processing.workers)
+        .build()
+        .map_err(|e| ScraperError::WorkerPool(e.to_string()))?;
+    log_info!(
+        "[main] Processing {} files with {} workers",
+        saved_files.len(),
+        pool.current_num_threads()
+    );
+    let extracted: Vec<_> = pool.install(|| {
+        saved_files
+            .into_par_iter()
+            .map(|(path, content)| {
+                log_info!("[main] Processing {:?}", path);
+                let result = Scraper::with_adapter(&content, adapter.clone())
+                    .content()
+                    .with_base_url(base_url)
+                    .with_min_success_ratio(config.scraper.min_success_ratio)
+                    .extract_companies_data();
+                (path, result)
+            })
+            .collect()
+    });
+
+    let mut pages = Vec::new();
+    let mut total_companies = 0;
+    let mut seen_profiles = HashSet::new();
+    let mut duplicates = 0;
+
+    let file_count = extracted.len();
+    for (index, (path, result)) in extracted.into_iter().enumerate() {
+        let mut companies_data = match result {
+            Ok(companies_data) => companies_data,
+            Err(e @ ScraperError::LowExtractionRatio { .. }) => {
+                log_error!("[main] {} in {:?}, aborting", e, path);
+                return Err(e.into());
+            }
+            Err(e) => {
+                log_error!("[main] Skipping {:?}: {}", path, e);
+                continue;
+            }
+        };
+
+        if companies_data.is_empty() {
+            log_error!("[main] No companies found in {:?}", path);
+            continue;
+        }
+
+        if config.dedupe {
+            let extracted = companies_data.len();
+            companies_data = utils::dedupe_unseen_companies(&mut seen_profiles, companies_data);
+            duplicates += extracted - companies_data.len();
+        }
+
+        if let Some(max_companies) = config.max_companies {
+            companies_data.truncate(max_companies - total_companies);
+        }
+        total_companies += companies_data.len();
+        metrics.companies_extracted(companies_data.len());
+
+        if !config.output.redact_fields.is_empty() {
+            for company_data in companies_data.iter_mut() {
+                company_data.redact(&config.output.redact_fields, config.output.redact_mode);
+            }
+        }
+
+        let limit_reached = config
+            .max_companies
+            .filter(|&max_companies| total_companies >= max_companies);
+        if let Some(max_companies) = limit_reached {
+            log_info!(
+                "[main] max_companies limit of {} reached in {:?}, skipping {} remaining files",
+                max_companies,
+                path,
+                file_count - index - 1
+            );
+        }
+
+        pages.push(PageCompanies {
+            source: path,
+            companies: companies_data,
+        });
+
+        if limit_reached.is_some() {
+            break;
+        }
+    }
+
+    if config.dedupe {
+        log_info!(
+            "[main] Dropped {} duplicate companies across pages",
+            duplicates
+        );
+    }
+
+    let mut snapshot = None;
+    if let Some(previous_file) = &config.output.previous_file {
+        let metadata = ScrapeMetadata {
+            scraped_at: utils::now_rfc3339(),
+            base_url: base_url.clone(),
+            start_path: config.start_path.clone(),
+            total_companies,
+        };
+        let (changed, current) =
+            output::keep_changed(&config.output, Path::new(previous_file), &metadata, pages)?;
+        pages = changed;
+        snapshot = Some((previous_file, current));
+    }
+
+    match config.output.sink {
+        OutputSinkKind::File => output::send_pages(&FileSink::new(config), &pages).await?,
+        OutputSinkKind::Webhook => output::send_pages(&WebhookSink::new(config)?, &pages).await?,
+    }
+
+    // Only replaced once the sink took the changes, so they are diffed and
+    // sent again if this run failed to deliver them
+    if let Some((previous_file, snapshot)) = snapshot {
+        utils::save_json(&snapshot, previous_file)?;
+    }
+
+    log_info!(
+        "[main] Processing completed, extracted {} companies",
+        total_companies
+    );
+    Ok(ProcessingSummary {
+        pages: file_count,
+        companies: total_companies,
+        duplicates,
+    })
+}
+
+// State shared by every page fetch of one download phase
+struct DownloadContext<'a> {
+    config: &'a Config,
+    proxy_manager: &'a ProxyManager,
+    retry_budget: RetryBudget,
+    breaker: CircuitBreaker,
+    // Built once from `config.selectors` so every check sees the same markup
+    adapter: ClutchAdapter,
+    deadline: Option<tokio::time::Instant>,
+    // One client per proxy, kept across pages so cookies set by one page are
+    // sent with the next, like a browser session
+    clients: Mutex<HashMap<String, Client>>,
+}
+
+impl<'a> DownloadContext<'a> {
+    fn new(
+        config: &'a Config,
+        proxy_manager: &'a ProxyManager,
+        deadline: Option<tokio::time::Instant>,
+    ) -> Self {
+        Self {
+            config,
+            proxy_manager,
+            retry_budget: RetryBudget::new(
+                config.max_retries,
+                Duration::from_secs(config.retry.window_secs),
+            ),
+            breaker: CircuitBreaker::new(config.abort_after_consecutive_failures),
+            adapter: ClutchAdapter::new(&config.selectors),
+            deadline,
+            clients: Default::default(),
+        }
+    }
+
+    fn scraper(&self, html: &str) -> Scraper<ClutchAdapter> {
+        Scraper::with_adapter(html, self.adapter.clone())
+    }
+
+    // The proxy's client with its current (possibly adaptive) timeout
+    async fn client_for(&self, proxy: &str) -> Result<Client> {
+        let timeout = self.proxy_manager.request_timeout_for(proxy).await;
+        let cached = self.clients.lock().unwrap().get(proxy).cloned();
+        let client = match cached {
+            Some(client) => client,
+            None => {
+                let client = build_client(self.config, self.proxy_manager, proxy).await?;
+                self.clients
+                    .lock()
+                    .unwrap()
+                    .insert(proxy.to_string(), client.clone());
+                client
+            }
+        };
+        Ok(client.with_timeout(timeout))
+    }
+}
+
+// Fetches the first page, then the rest either concurrently (when the total
+// page count is known) or by following the "next" link
+async fn download_pages(
+    config: &Config,
+    proxy_manager: &ProxyManager,
+    deadline: Option<tokio::time::Instant>,
+) -> Result<()> {
+    let download = DownloadContext::new(config, proxy_manager, deadline);
+    let resumed = if config.resume {
+        match checkpoint_resume_point(config, proxy_manager).await? {
+            Some(page_info) => Some(page_info),
+            None => resume_point(&download)?,
+        }
+    } else {
+        None
+    };
+
+    // Pages fetched this run, counted against `config.max_pages`
+    let mut fetched = 0;
+    let page_info = match resumed {
+        Some(page_info) => {
+            if page_info.next_url.is_none() {
+                log_info!(
+                    "[main] Saved page {} is the last page, skipping download phase",
+                    page_info.current_page
+                );
+                return Ok(());
+            }
+            log_info!(
+                "[main] Resuming download after saved page {}",
+                page_info.current_page
+            );
+            page_info
+        }
+        None => {
+            let first_path = page_path(&config.start_path, config.start_page);
+            let Some(content) = fetch_page(&download, &first_path, config.start_page).await? else {
+                return Ok(());
+            };
+
+            let page_info = download
+                .scraper(&content)
+                .page()
+                .with_base_url(&config.base_url)
+                .with_current_path(&first_path)
+                .analyze()?;
+            save_checkpoint(config, proxy_manager, &first_path, &page_info).await;
+            fetched += 1;
+            page_info
+        }
+    };
+
+    match page_info.total_pages {
+        Some(total_pages) if config.concurrency > 1 && total_pages > page_info.current_page => {
+            // Like the sequential path, only a resumed run keeps pages saved earlier
+            let mut pages: Vec<usize> = (page_info.current_page + 1..=total_pages)
+                .filter(|&page_number| {
+                    !config.resume || !utils::is_page_saved(&config.storage.html_dir, page_number)
+                })
+                .collect();
+            if let Some(max_pages) = config.max_pages {
+                let remaining = max_pages.saturating_sub(fetched);
+                if pages.len() > remaining {
+                    log_info!(
+                        "[main] max_pages limit of {} reached, skipping {} pages",
+                        max_pages,
+                        pages.len() - remaining
+                    );
+                    pages.truncate(remaining);
+                }
+            }
+            download_concurrently(&download, pages).await
+        }
+        _ => download_sequentially(&download, page_info, fetched).await,
+    }
+}
+
+// Seeds the loop from the checkpoint file, ignoring checkpoints from another site
+async fn checkpoint_resume_point(
+    config: &Config,
+    proxy_manager: &ProxyManager,
+) -> Result<Option<PageInfo>> {
+    let Some(checkpoint_file) = &config.storage.checkpoint_file else {
+        return Ok(None);
+    };
+
+    let checkpoint = match utils::read_checkpoint(Path::new(checkpoint_file)) {
+        Ok(Some(checkpoint)) => checkpoint,
+        Ok(None) => return Ok(None),
+        Err(e) => {
+            log_warn!(
+                "[main] Ignoring unreadable checkpoint {}: {}",
+                checkpoint_file,
+                e
+            );
+            return Ok(None);
+        }
+    };
+
+    if checkpoint.base_url != config.base_url {
+        log_warn!(
+            "[main] Checkpoint {} is for {} but base_url is {}, starting fresh",
+            checkpoint_file,
+            checkpoint.base_url,
+            config.base_url
+        );
+        return Ok(None);
+    }
+
+    log_info!(
+        "[main] Loaded checkpoint saved at {} (page {}, {})",
+        checkpoint.saved_at,
+        checkpoint.page.current_page,
+        checkpoint.current_path
+    );
+    proxy_manager.restore_stats(checkpoint.proxy_stats).await;
+    Ok(Some(checkpoint.page))
+}
+
+async fn save_checkpoint(
+    config: &Config,
+    proxy_manager: &ProxyManager,
+    current_path: &str,
+    page_info: &PageInfo,
+) {
+    let Some(checkpoint_file) = &config.storage.checkpoint_file else {
+        return;
+    };
+
+    let checkpoint = Checkpoint {
+        base_url: config.base_url.clone(),
+        start_path: config.start_path.clone(),
+        current_path: current_path.to_string(),
+        page: page_info.clone(),
+        saved_at: utils::now_rfc3339(),
+        proxy_stats: proxy_manager.stats_snapshot().await,
+    };
+    if let Err(e) = utils::write_checkpoint(Path::new(checkpoint_file), &checkpoint) {
+        log_error!("[main] Failed to write checkpoint: {}", e);
+    }
+}
+
+// Analyzes the highest page already in the HTML directory so the crawl can pick up after it
+fn resume_point(download: &DownloadContext<'_>) -> Result<Option<PageInfo>> {
+    let config = download.config;
+    let Some(page_number) = utils::highest_saved_page(&config.storage.html_dir)? else {
+        log_info!("[main] No saved pages found, starting from the first page");
+        return Ok(None);
+    };
+
+    let Some(path) = utils::saved_html_path(&config.storage.html_dir, page_number) else {
+        return Ok(None);
+    };
+    let content = utils::read_html(&path)?;
+    let page_info = download
+        .scraper(&content)
+        .page()
+        .with_base_url(&config.base_url)
+        .with_current_path(page_path(&config.start_path, page_number))
+        .analyze()?;
+    Ok(Some(page_info))
+}
+
+// Follows the "next" link page by page until the last page is reached
+async fn download_sequentially(
+    download: &DownloadContext<'_>,
+    mut page_info: PageInfo,
+    mut fetched: usize,
+) -> Result<()> {
+    let DownloadContext {
+        config,
+        proxy_manager,
+        deadline,
+        ..
+    } = *download;
+    // Paths already fetched, normalized so "?page=0" and no query compare equal,
+    // to stop on pagers that link back to an earlier page
+    let normalize = |path: &str| page_path(path, page_number_of(path));
+    let mut visited = HashSet::from([normalize(&page_path(
+        &config.start_path,
+        page_info.current_page,
+    ))]);
+
+    loop {
+        log_info!(
+            "[main] Processing page {}/{} of results ({} on this page, {} in total)",
+            page_info.current_page,
+            page_info.total_pages.unwrap_or(0),
+            page_info.results_on_page,
+            page_info
+                .total_results
+                .map_or("unknown".to_string(), |total| total.to_string())
+        );
+
+        let Some(next_url) = page_info.next_url else {
+            log_info!("[main] Reached last page ({})", page_info.current_page);
+            return Ok(());
+        };
+
+        if let Some(max_pages) = config.max_pages.filter(|&max_pages| fetched >= max_pages) {
+            log_info!(
+                "[main] max_pages limit of {} reached after page {}, stopping",
+                max_pages,
+                page_info.current_page
+            );
+            return Ok(());
+        }
+
+        if past_deadline(deadline) {
+            log_warn!(
+                "[main] Run deadline reached after {} pages, stopping before page {}",
+                proxy_manager.metrics().pages_fetched(),
+                page_info.current_page + 1
+            );
+            return Ok(());
+        }
+
+        let current_path = next_url.replace(&config.base_url, "");
+        if !visited.insert(normalize(&current_path)) {
+            log_warn!(
+                "[main] Next page {} was already fetched, stopping to avoid a pagination loop",
+                current_path
+            );
+            return Ok(());
+        }
+
+        // Add a small, irregular delay between successful requests
+        tokio::time::sleep(config.request_delay()).await;
+
+        let page_number = page_info.current_page + 1;
+        let Some(content) = fetch_page(download, &current_path, page_number).await? else {
+            return Ok(());
+        };
+
+        page_info = download
+            .scraper(&content)
+            .page()
+            .with_base_url(&config.base_url)
+            .with_current_path(&current_path)
+            .analyze()?;
+        save_checkpoint(config, proxy_manager, &current_path, &page_info).await;
+        fetched += 1;
+    }
+}
+
+// Fetches the given pages with at most `config.concurrency` requests in flight
+async fn download_concurrently(download: &DownloadContext<'_>, pages: Vec<usize>) -> Result<()> {
+    let DownloadContext {
+        config, deadline, ..
+    } = *download;
+    log_info!(
+        "[main] Downloading {} pages with {} workers",
+        pages.len(),
+        config.concurrency
+    );
+
+    let mut pages = pages.into_iter();
+    let mut tasks = FuturesUnordered::new();
+    let mut failed_pages = Vec::new();
+
+    loop {
+        if pages.len() > 0 && past_deadline(deadline) {
+            log_warn!(
+                "[main] Run deadline reached after {} pages, not starting the remaining {}",
+                download.proxy_manager.metrics().pages_fetched(),
+                pages.len()
+            );
+            pages = Vec::new().into_iter();
+        }
+
+        while tasks.len() < config.concurrency {
+            let Some(page_number) = pages.next() else {
+                break;
+            };
+            let path = page_path(&config.start_path, page_number);
+            tasks.push(async move {
+                let result = fetch_page(download, &path, page_number).await;
+                if matches!(result, Ok(Some(_))) {
+                    // Keep each worker from firing requests back to back
+                    tokio::time::sleep(config.request_delay()).await;
+                }
+                (page_number, result)
+            });
+        }
+
+        let Some((page_number, result)) = tasks.next().await else {
+            break;
+        };
+        if result?.is_none() {
+            failed_pages.push(page_number);
+        }
+    }
+
+    if !failed_pages.is_empty() {
+        failed_pages.sort_unstable();
+        log_warn!("[main] Failed to download pages: {:?}", failed_pages);
+    }
+    Ok(())
+}
+
+async fn build_client(
+    config: &Config,
+    proxy_manager: &ProxyManager,
+    proxy: &str,
+) -> Result<Client> {
+    Client::builder()
+        .base_url(&config.base_url)
+        .header(
+            "user-agent",
+            client::pick_user_agent(&config.client.user_agents, &config.client.impersonate),
+        )?
+        .header("accept-language", &config.client.accept_language)?
+        .proxy(proxy)
+        .impersonate(client::parse_impersonation(&config.client.impersonate)?)
+        .cookie_store(true)
+        .timeout(proxy_manager.request_timeout_for(proxy).await)
+        .redirect(match config.client.max_redirects {
+            0 => RedirectPolicy::None,
+            max => RedirectPolicy::Limited(max),
+        })
+        .trace_body_chars(config.client.trace_body_chars)
+        .build()
+}
+
+// Sends a HEAD to base_url through one validated proxy so a site that blocks
+// every proxy is caught before the crawl starts. Only 403/503 abort the run;
+// other failures are logged and left to the regular retry logic.
+async fn preflight(config: &Config, proxy_manager: &ProxyManager) -> Result<()> {
+    let proxy = match proxy_manager.get_proxy().await {
+        Ok(proxy) => proxy,
+        Err(e) => {
+            log_warn!("[main] Skipping preflight, no proxy available: {}", e);
+            return Ok(());
+        }
+    };
+
+    log_info!(
+        "[main] Preflight: checking {} via proxy {}",
+        config.base_url,
+        proxy
+    );
+    let client = match build_client(config, proxy_manager, &proxy).await {
+        Ok(client) => client,
+        Err(e) => {
+            proxy_manager.release_proxy(&proxy).await;
+            return Err(e);
+        }
+    };
+    let result = match client.head("/").await {
+        // Some servers don't implement HEAD, fall back to a regular GET
+        Err(AppError::Client(ClientError::Status(405 | 501))) => client.get("/").await,
+        result => result,
+    };
+    proxy_manager.release_proxy(&proxy).await;
+
+    match result {
+        Ok(response) => {
+            log_info!(
+                "[main] Preflight: {} answered {} via proxy {}",
+                config.base_url,
+                response.status,
+                proxy
+            );
+            Ok(())
+        }
+        Err(AppError::Client(ClientError::Status(status @ (403 | 503)))) => {
+            log_error!(
+                "[main] Preflight: {} answered {} via proxy {}, the site is likely blocking the proxies. Aborting before the crawl.",
+                config.base_url,
+                status,
+                proxy
+            );
+            Err(ClientError::PreflightBlocked {
+                url: config.base_url.clone(),
+                status,
+            }
+            .into())
+        }
+        Err(e) => {
+            log_warn!(
+                "[main] Preflight: request to {} via proxy {} failed, continuing: {}",
+                config.base_url,
+                proxy,
+                e
+            );
+            Ok(())
+        }
+    }
+}
+
+// Downloads and saves a single page, rotating proxies until it succeeds or
+// the retry budget runs out. Returns `None` when the page could not be fetched.
+async fn fetch_page(
+    download: &DownloadContext<'_>,
+    current_path: &str,
+    page_number: usize,
+) -> Result<Option<String>> {
+    let DownloadContext {
+        config,
+        proxy_manager,
+        ref retry_budget,
+        ref breaker,
+        ref adapter,
+        ..
+    } = *download;
+    let base_url = &config.base_url;
+    // Proxy switches for this page, only used to grow the backoff
+    let mut attempt = 0;
+    let referer = referer_headers(config, current_path, page_number);
+
+    loop {
+        log_info!(
+            "[main] Fetching page {} from: {}{}",
+            page_number,
+            base_url,
+            current_path
+        );
+
+        // Get a proxy
+        let proxy = match proxy_manager.get_proxy().await {
+            Ok(p) => p,
+            Err(e) => {
+                log_error!("[main] Failed to get proxy: {}", e);
+                if !retry_budget.can_retry() {
+                    log_info!("[main] Retry budget exhausted, stopping.");
+                    return Ok(None);
+                }
+                retry_budget.record_attempt();
+                log_info!(
+                    "[main] Waiting {} seconds before retry...",
+                    config.retry_delay
+                );
+                tokio::time::sleep(Duration::from_secs(config.retry_delay)).await;
+                continue;
+            }
+        };
+
+        log_info!(
+            "[main] Using proxy: {} (attempt {}, {}/{} retries used in the window)",
+            proxy,
+            attempt + 1,
+            retry_budget.used(),
+            retry_budget.max_attempts()
+        );
+
+        let client = match download.client_for(&proxy).await {
+            Ok(client) => client,
+            Err(e) => {
+                proxy_manager.release_proxy(&proxy).await;
+                return Err(e);
+            }
+        };
+
+        // Make request
+        proxy_manager.wait_for_rate_limit().await;
+        let request_started = Instant::now();
+        let request = client.get_with_retries(
+            current_path,
+            &referer,
+            config.retry.transient_retries,
+            config.retry.server_error_retries,
+            Duration::from_secs(config.retry_base_delay()),
+        );
+        let result = request.await.map(|(response, attempts)| {
+            if attempts > 1 {
+                log_info!(
+                    "[main] Got a response for page {} after {} attempts via proxy {}",
+                    page_number,
+                    attempts,
+                    proxy
+                );
+            }
+            response
+        });
+        let failure = match result {
+            Ok(response) if response.is_redirect() => {
+                let reason = format!(
+                    "Redirected ({}) to {}",
+                    response.status,
+                    response.location.as_deref().unwrap_or("unknown location")
+                );
+                log_warn!("[main] {} via proxy {}, switching proxies", reason, proxy);
+                proxy_manager
+                    .mark_proxy_failure(&proxy, &reason, Some(response.status), current_path)
+                    .await?;
+                reason
+            }
+            // Sending late pages back to page 1 is a known anti-bot trap; saving
+            // the result would restart the crawl forever
+            Ok(response) if page_number_of(&response.final_url) != page_number_of(current_path) => {
+                log_warn!(
+                    "[main] Requested page {} ({}) but ended up at {}, stopping to avoid a redirect loop",
+                    page_number,
+                    current_path,
+                    response.final_url
+                );
+                proxy_manager.release_proxy(&proxy).await;
+                return Ok(None);
+            }
+            Ok(response) if response.content.len() < config.scraper.min_content_length => {
+                let reason = format!(
+                    "Body of {} bytes is shorter than scraper.min_content_length ({})",
+                    response.content.len(),
+                    config.scraper.min_content_length
+                );
+                log_warn!("[main] {} via proxy {}, retrying", reason, proxy);
+                proxy_manager
+                    .mark_proxy_failure(&proxy, &reason, Some(response.status), current_path)
+                    .await?;
+                reason
+            }
+            Ok(response) if response.is_challenge_page() => {
+                let reason = format!(
+                    "Challenge page ({})",
+                    response.challenge_marker().unwrap_or_default()
+                );
+                log_error!("[main] Received {} from proxy {}", reason, proxy);
+                proxy_manager
+                    .mark_proxy_failure(&proxy, &reason, Some(response.status), current_path)
+                    .await?;
+                reason
+            }
+            Ok(response) => {
+                let missing_list = download
+                    .scraper(&response.content)
+                    .content()
+                    .check_providers_list()
+                    .err();
+                let underfilled = (missing_list.is_none() && retry_budget.can_retry())
+                    .then(|| underfilled_count(config, adapter, &response.content))
+                    .flatten();
+
+                if let Some(e) = missing_list {
+                    let reason = e.to_string();
+                    log_warn!("[main] {} via proxy {}, retrying", reason, proxy);
+                    proxy_manager
+                        .mark_proxy_failure(&proxy, &reason, Some(response.status), current_path)
+                        .await?;
+                    reason
+                } else if let Some(found) = underfilled {
+                    let reason = format!(
+                        "Under-filled page: {} of {} expected providers",
+                        found, config.expected_per_page
+                    );
+                    log_warn!("[main] {} via proxy {}, retrying", reason, proxy);
+                    proxy_manager
+                        .mark_proxy_failure(&proxy, &reason, Some(response.status), current_path)
+                        .await?;
+                    reason
+                } else {
+                    proxy_manager
+                        .mark_proxy_success(
+                            &proxy,
+                            current_path,
+                            response.status,
+                            request_started.elapsed(),
+                        )
+                        .await?;
+                    breaker.record_success();
+
+                    log_info!(
+                        "[main] Received response: Status: {}, Content Length: {} bytes",
+                        response.status,
+                        response.content.len()
+                    );
+
+                    // Save the HTML content
+                    let saved_path =
+                        utils::save_html(&response.content, page_number, &config.storage)?;
+                    log_info!("[main] Saved page {} to {:?}", page_number, saved_path);
+                    proxy_manager.metrics().page_fetched();
+
+                    return Ok(Some(response.content));
+                }
+            }
+            Err(e) if e.is_page_error() => {
+                // A missing page (404, 410) won't show up through another proxy,
+                // so don't blame this one and give up on the page
+                log_error!(
+                    "[main] Giving up on page {} after permanent error via proxy {}: {}",
+                    page_number,
+                    proxy,
+                    e
+                );
+                proxy_manager.release_proxy(&proxy).await;
+                return Ok(None);
+            }
+            Err(e) => {
+                let error_msg = format!("{}", e);
+                log_error!("[main] Request failed with proxy {}: {}", proxy, error_msg);
+                let status = match &e {
+                    AppError::Client(ClientError::Status(status)) => Some(*status),
+                    _ => None,
+                };
+                proxy_manager
+                    .mark_proxy_failure(&proxy, &error_msg, status, current_path)
+                    .await?;
+                error_msg
+            }
+        };
+
+        if let Some(failures) = breaker.record_failure() {
+            log_error!(
+                "[main] {} fetch attempts failed in a row (last on page {}: {}), aborting the download phase",
+                failures,
+                page_number,
+                failure
+            );
+            return Err(ScraperError::ConsecutiveFailures { failures }.into());
+        }
+
+        if !retry_budget.can_retry() {
+            log_warn!(
+                "[main] Retry budget exhausted on page {}, stopping. Last error: {}",
+                page_number,
+                failure
+            );
+            return Ok(None);
+        }
+        retry_budget.record_attempt();
+        attempt += 1;
+
+        let delay = utils::backoff(
+            attempt,
+            Duration::from_secs(config.retry_base_delay()),
+            Duration::from_secs(config.retry.max_delay),
+        );
+        log_info!("[main] Waiting {:.1?} before switching proxy...", delay);
+        tokio::time::sleep(delay).await;
+    }
+}
+
+// Points `Referer` at the previous listing page, as if the visitor had clicked
+// "next"; the first page is requested without one
+fn referer_headers(config: &Config, current_path: &str, page_number: usize) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if page_number <= 1 {
+        return headers;
+    }
+
+    let previous = page_path(current_path, page_number - 1);
+    let referer = url::Url::parse(&config.base_url)
+        .and_then(|base| base.join(&previous))
+        .ok()
+        .and_then(|url| HeaderValue::from_str(url.as_str()).ok());
+    if let Some(referer) = referer {
+        headers.insert(header::REFERER, referer);
+    }
+    headers
+}
+
+// Returns the provider count when a page that isn't the last one holds fewer
+// providers than expected, which usually means the listing only partially loaded
+fn underfilled_count(config: &Config, adapter: &ClutchAdapter, content: &str) -> Option<usize> {
+    if config.expected_per_page == 0 {
+        return None;
+    }
+
+    let scraper = Scraper::with_adapter(content, adapter.clone());
+    let found = scraper.content().provider_count();
+    let threshold = (config.expected_per_page as f64 * config.underfill_threshold).ceil() as usize;
+    if found >= threshold {
+        return None;
+    }
+
+    // The last page is allowed to be short
+    let is_last_page = scraper
+        .page()
+        .with_base_url(&config.base_url)
+        .analyze()
+        .map(|page_info| page_info.next_url.is_none())
+        .unwrap_or(false);
+    (!is_last_page).then_some(found)
+}
+
+// Judges the run by content rather than HTTP status so CI can gate on it
+fn check_min_companies(found: usize, required: usize) -> Result<()> {
+    if found < required {
+        log_error!(
+            "[main] Extracted {} companies, below the required {}",
+            found,
+            required
+        );
+        return Err(ScraperError::InsufficientCompanies { found, required }.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,266 @@
+use super::{
+    check_min_companies, download_pages, fetch_page, scrape_categories, underfilled_count,
+    wait_initial_delay, DownloadContext, Shutdown,
+};
+use crate::config::Config;
+use crate::error::{AppError, ScraperError};
+use crate::proxy::ProxyManager;
+use crate::scraper::ClutchAdapter;
+use crate::utils;
+use futures::FutureExt;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+fn fixture(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("reading {:?}: {}", path, e))
+}
+
+fn test_config(html_dir: &Path) -> Config {
+    let mut config: Config = toml::from_str("").unwrap();
+    config.base_url = "http://clutch.test".to_string();
+    config.storage.html_dir = html_dir.display().to_string();
+    config
+}
+
+async fn read_request_head(stream: &mut TcpStream) -> String {
+    let mut head = Vec::new();
+    let mut buf = [0; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut buf).await.unwrap();
+        if read == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..read]);
+    }
+    String::from_utf8_lossy(&head).into_owned()
+}
+
+// Plain HTTP proxy that answers every request itself with the listing fixture.
+// The first response sets a session cookie; the Cookie header of each request
+// is recorded.
+async fn spawn_cookie_proxy() -> (String, Arc<Mutex<Vec<Option<String>>>>) {
+    spawn_page_proxy(vec![fixture("listing.html")]).await
+}
+
+// Like `spawn_cookie_proxy`, but answers with `bodies` in turn, repeating the
+// last one once they run out
+async fn spawn_page_proxy(bodies: Vec<String>) -> (String, Arc<Mutex<Vec<Option<String>>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let proxy_url = format!("http://{}", listener.local_addr().unwrap());
+    let cookies = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&cookies);
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let head = read_request_head(&mut stream).await;
+            let cookie = head.lines().find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("cookie")
+                    .then(|| value.trim().to_string())
+            });
+            let served = {
+                let mut recorded = recorded.lock().unwrap();
+                recorded.push(cookie);
+                recorded.len() - 1
+            };
+            let body = &bodies[served.min(bodies.len() - 1)];
+            let set_cookie = if served == 0 {
+                "Set-Cookie: session=abc; Path=/\r\n"
+            } else {
+                ""
+            };
+            let response = format!(
+                "HTTP/1.1 200 OK\r\n{}Content-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                set_cookie,
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            let _ = stream.shutdown().await;
+        }
+    });
+    (proxy_url, cookies)
+}
+
+#[tokio::test]
+async fn cookies_from_one_page_are_sent_with_the_next() {
+    let (proxy_url, cookies) = spawn_cookie_proxy().await;
+    let html_dir = utils::scratch_dir("engine-cookies");
+    let config = test_config(&html_dir);
+    let proxy_manager = ProxyManager::with_working_proxies(&[&proxy_url], config.clone()).await;
+    let download = DownloadContext::new(&config, &proxy_manager, None);
+
+    let first = fetch_page(&download, "/developers/rust", 1).await.unwrap();
+    let second = fetch_page(&download, "/developers/rust?page=1", 2)
+        .await
+        .unwrap();
+    assert!(first.is_some() && second.is_some());
+
+    assert_eq!(
+        *cookies.lock().unwrap(),
+        [None, Some("session=abc".to_string())]
+    );
+    std::fs::remove_dir_all(&html_dir).unwrap();
+}
+
+#[tokio::test]
+async fn failed_runs_still_write_an_unsuccessful_summary() {
+    let dir = utils::scratch_dir("engine-summary");
+    let mut config = test_config(&dir);
+    config.proxy.file = dir.join("missing-proxies.txt").display().to_string();
+    let summary_file = dir.join("summary.json");
+    config.summary_file = Some(summary_file.display().to_string());
+
+    assert!(super::run(config).await.is_err());
+
+    let summary: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&summary_file).unwrap()).unwrap();
+    assert_eq!(summary["success"], false);
+    assert_eq!(summary["pages_downloaded"], 0);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn short_pages_are_underfilled_unless_last() {
+    let dir = utils::scratch_dir("engine-underfill");
+    let mut config = test_config(&dir);
+    config.expected_per_page = 10;
+    config.underfill_threshold = 0.8;
+    let adapter = ClutchAdapter::new(&config.selectors);
+
+    // Two providers on a page that links to a next one
+    let listing = fixture("listing.html");
+    assert_eq!(underfilled_count(&config, &adapter, &listing), Some(2));
+
+    let last_page = listing.replace(
+        r#"<li class="sg-pagination-v2-next"><a href="/developers/rust?page=1">Next</a></li>"#,
+        "",
+    );
+    assert_eq!(underfilled_count(&config, &adapter, &last_page), None);
+
+    config.expected_per_page = 2;
+    assert_eq!(underfilled_count(&config, &adapter, &listing), None);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn min_companies_threshold_is_inclusive() {
+    assert!(matches!(
+        check_min_companies(4, 5),
+        Err(AppError::Scraper(ScraperError::InsufficientCompanies {
+            found: 4,
+            required: 5
+        }))
+    ));
+    assert!(check_min_companies(5, 5).is_ok());
+    assert!(check_min_companies(6, 5).is_ok());
+    // The default threshold of zero accepts an empty run
+    assert!(check_min_companies(0, 0).is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+async fn waits_the_initial_delay_before_downloading() {
+    let dir = utils::scratch_dir("engine-initial-delay");
+    let mut config = test_config(&dir);
+    let start = tokio::time::Instant::now();
+
+    wait_initial_delay(&config).await;
+    assert_eq!(start.elapsed(), std::time::Duration::ZERO);
+
+    config.initial_delay_secs = 30;
+    wait_initial_delay(&config).await;
+    assert_eq!(start.elapsed(), std::time::Duration::from_secs(30));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn proxy_stats_cover_every_category() {
+    let (proxy_url, requests) = spawn_cookie_proxy().await;
+    let dir = utils::scratch_dir("engine-categories");
+    let mut config = test_config(&dir.join("html"));
+    config.max_pages = Some(1);
+    config.request_delay_min = 0;
+    config.request_delay_max = 0;
+    config.storage.json_dir = dir.join("json").display().to_string();
+    config.output.json_file = dir.join("companies.json").display().to_string();
+    config.proxy.stats_file = Some(dir.join("proxy_stats.json").display().to_string());
+    let proxy_manager = ProxyManager::with_working_proxies(&[&proxy_url], config.clone()).await;
+    let shutdown: Shutdown = futures::future::pending().boxed().shared();
+
+    let categories = ["/developers/rust".to_string(), "/developers/go".to_string()];
+    let processed = scrape_categories(&config, &proxy_manager, &categories, None, &shutdown)
+        .await
+        .unwrap();
+    assert_eq!(processed.pages, 2);
+    assert_eq!(requests.lock().unwrap().len(), 2);
+
+    // Both categories' requests survive the per-category resets
+    let stats = &proxy_manager.stats_snapshot().await[&proxy_url];
+    assert_eq!(stats.total_requests, 2);
+    assert_eq!(stats.successful_requests, 2);
+
+    proxy_manager.save_stats().await.unwrap();
+    let saved: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.join("proxy_stats.json")).unwrap())
+            .unwrap();
+    assert_eq!(saved["stats"][&proxy_url]["total_requests"], 2);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn concurrent_downloads_refetch_saved_pages_unless_resuming() {
+    let (proxy_url, requests) = spawn_cookie_proxy().await;
+    let html_dir = utils::scratch_dir("engine-concurrent-stale");
+    let mut config = test_config(&html_dir);
+    config.concurrency = 2;
+    config.request_delay_min = 0;
+    config.request_delay_max = 0;
+    utils::save_html("stale", 2, &config.storage).unwrap();
+    let proxy_manager = ProxyManager::with_working_proxies(&[&proxy_url], config.clone()).await;
+
+    download_pages(&config, &proxy_manager, None).await.unwrap();
+
+    // The listing reports three pages; none is skipped without `resume`
+    assert_eq!(requests.lock().unwrap().len(), 3);
+    let page_2 = utils::saved_html_path(&config.storage.html_dir, 2).unwrap();
+    assert_ne!(std::fs::read_to_string(page_2).unwrap(), "stale");
+    std::fs::remove_dir_all(&html_dir).unwrap();
+}
+
+#[tokio::test]
+async fn underfilled_pages_are_fetched_again() {
+    // Same listing with a third card, as if the first response had only
+    // partially loaded
+    let short = fixture("listing.html");
+    let third_card = r#"<li class="provider-list-item" data-clutch-pid="3">
+      <h3 class="provider__title">
+        <a class="provider__title-link" href="/profile/unsafe-cell">Unsafe Cell</a>
+      </h3>
+      <div itemprop="address" itemscope itemtype="http://schema.org/PostalAddress">
+        <meta itemprop="addressCountry" content="SE">
+        <meta itemprop="addressLocality" content="Malmo">
+      </div>
+    </li>
+  </ul>"#;
+    let full = short.replacen("</ul>", third_card, 1);
+    let (proxy_url, requests) = spawn_page_proxy(vec![short, full.clone()]).await;
+
+    let html_dir = utils::scratch_dir("engine-underfill-retry");
+    let mut config = test_config(&html_dir);
+    config.expected_per_page = 3;
+    config.underfill_threshold = 1.0;
+    config.retry.base_delay = Some(0);
+    let proxy_manager = ProxyManager::with_working_proxies(&[&proxy_url], config.clone()).await;
+    let download = DownloadContext::new(&config, &proxy_manager, None);
+
+    let page = fetch_page(&download, "/developers/rust", 1).await.unwrap();
+
+    assert_eq!(requests.lock().unwrap().len(), 2);
+    assert_eq!(page.as_deref(), Some(full.as_str()));
+    std::fs::remove_dir_all(&html_dir).unwrap();
+}
@@ -1,3 +1,4 @@
+use crate::proxy::ValidationSummary;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -37,6 +38,9 @@ pub enum ConfigError {
 
     #[error("Invalid configuration value: {0}")]
     InvalidValue(String),
+
+    #[error("Invalid command line argument: {0}")]
+    InvalidArgument(String),
 }
 
 #[derive(Error, Debug)]
@@ -47,17 +51,73 @@ pub enum ClientError {
     #[error("Request failed: {0}")]
     RequestFailed(String),
 
-    #[error("Response error {status_code}")]
-    ResponseError { status_code: u16, message: String },
+    #[error("Request timed out: {0}")]
+    Timeout(String),
+
+    #[error("Connection failed: {0}")]
+    Connect(String),
+
+    #[error("TLS error: {0}")]
+    Tls(String),
+
+    #[error("Failed to decode response: {0}")]
+    Decode(String),
+
+    #[error("Unexpected HTTP status {0}")]
+    Status(u16),
 
     #[error("Invalid URL: {0}")]
     InvalidUrl(String),
+
+    #[error("Too many redirects: {0}")]
+    TooManyRedirects(String),
+
+    #[error("Proxy {proxy} refused to tunnel the HTTPS request (CONNECT): {reason}")]
+    ProxyTunnelRefused { proxy: String, reason: String },
+
+    #[error("Preflight request to {url} was blocked with status {status}")]
+    PreflightBlocked { url: String, status: u16 },
+}
+
+impl ClientError {
+    // Whether the request may succeed if retried, possibly through another proxy.
+    // 403 and 429 count because Clutch blocks and throttles per IP.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ClientError::Timeout(_)
+            | ClientError::Connect(_)
+            | ClientError::ProxyTunnelRefused { .. }
+            | ClientError::TooManyRedirects(_) => true,
+            ClientError::Status(status) => matches!(status, 403 | 408 | 429 | 500..=599),
+            _ => false,
+        }
+    }
+
+    // Whether the page itself can't be fetched, whichever proxy is used. TLS,
+    // decoding and unclassified failures don't count: they usually come from a
+    // flaky proxy and clear up with another one.
+    pub fn is_page_error(&self) -> bool {
+        matches!(
+            self,
+            ClientError::Status(404 | 410) | ClientError::InvalidUrl(_)
+        )
+    }
+}
+
+impl AppError {
+    pub fn is_transient(&self) -> bool {
+        matches!(self, AppError::Client(e) if e.is_transient())
+    }
+
+    pub fn is_page_error(&self) -> bool {
+        matches!(self, AppError::Client(e) if e.is_page_error())
+    }
 }
 
 #[derive(Error, Debug)]
 pub enum ProxyError {
-    #[error("No working proxies available")]
-    NoWorkingProxies,
+    #[error("No working proxies available{}", summary.map(|summary| format!(" ({})", summary)).unwrap_or_default())]
+    NoWorkingProxies { summary: Option<ValidationSummary> },
 
     #[error("All proxies exhausted")]
     AllProxiesExhausted {
@@ -67,8 +127,20 @@ pub enum ProxyError {
     #[error("Proxy validation failed: {0}")]
     ValidationFailed(String),
 
+    #[error("Could not connect through proxy {0}")]
+    Unreachable(String),
+
     #[error("Proxy timeout: {0}")]
     TimeoutError(String),
+
+    #[error("Proxy {proxy} exits in {country}, which is not in proxy.allowed_countries")]
+    CountryNotAllowed { proxy: String, country: String },
+
+    #[error("Could not determine the exit country of {proxy}: {reason}")]
+    GeoLookupFailed { proxy: String, reason: String },
+
+    #[error("Failed to fetch the proxy list from {url}: {reason}")]
+    ListFetchFailed { url: String, reason: String },
 }
 
 #[derive(Error, Debug)]
@@ -78,6 +150,23 @@ pub enum ScraperError {
 
     #[error("Selector error: {0}")]
     SelectorError(String),
+
+    #[error("Blocked page detected ({reason}): {snippet}")]
+    BlockedPage { reason: String, snippet: String },
+
+    #[error(
+        "Extracted only {extracted} of {found} provider cards, below the minimum success ratio"
+    )]
+    LowExtractionRatio { extracted: usize, found: usize },
+
+    #[error("Extracted {found} companies, fewer than the required {required}")]
+    InsufficientCompanies { found: usize, required: usize },
+
+    #[error("Failed to start processing workers: {0}")]
+    WorkerPool(String),
+
+    #[error("Aborting the run after {failures} consecutive failed page fetches")]
+    ConsecutiveFailures { failures: usize },
 }
 
 pub type Result<T> = std::result::Result<T, AppError>;
@@ -0,0 +1,23 @@
+// Library entry point. `run` drives a whole scrape from a `Config`; the
+// binary in main.rs only parses the command line and sets up logging.
+mod block_page;
+pub mod cli;
+pub mod client;
+pub mod config;
+mod engine;
+pub mod error;
+pub mod logging;
+mod metrics;
+pub mod output;
+pub mod proxy;
+mod retry;
+pub mod scraper;
+pub mod utils;
+
+pub use client::ClientBuilder;
+pub use config::Config;
+pub use engine::{run, run_until};
+pub use error::{AppError, Result};
+pub use proxy::ProxyManager;
+pub use scraper::{extract_companies, CompanyData};
+pub use utils::RunSummary;
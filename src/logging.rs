@@ -1,8 +1,13 @@
+use crate::config::LogFormat;
 use crate::error::{AppError, ConfigError, Result};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use tracing::Level;
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
 use tracing_subscriber::{
-    fmt::{self, format::FmtSpan},
+    fmt::{self, format::FmtSpan, writer::BoxMakeWriter},
     layer::SubscriberExt,
     Layer, Registry,
 };
@@ -11,8 +16,15 @@ use tracing_subscriber::{
 pub struct LoggerConfig {
     pub directory: String,
     pub file_name: String,
+    pub error_file_name: Option<String>,
     pub rotation: Rotation,
+    // When set, files roll once they exceed this many bytes instead of by `rotation`
+    pub max_size_bytes: Option<u64>,
+    pub max_files: usize,
     pub level: Level,
+    pub format: LogFormat,
+    // Adds a layer that prints every event to stdout next to the file
+    pub stdout: bool,
 }
 
 impl Default for LoggerConfig {
@@ -20,13 +32,34 @@ impl Default for LoggerConfig {
         Self {
             directory: "logs".to_string(),
             file_name: "scraper.log".to_string(),
+            error_file_name: None,
             rotation: Rotation::DAILY,
+            max_size_bytes: None,
+            max_files: 5,
             level: Level::INFO,
+            format: LogFormat::Pretty,
+            stdout: true,
         }
     }
 }
 
 pub fn init_logging(config: LoggerConfig) -> Result<()> {
+    let subscriber = build_subscriber(&config)?;
+
+    // Set the subscriber as the default
+    tracing::subscriber::set_global_default(subscriber).map_err(|e| {
+        AppError::Config(ConfigError::InvalidValue(format!(
+            "Failed to set global subscriber: {}",
+            e
+        )))
+    })?;
+
+    Ok(())
+}
+
+// Every layer `config` asks for, without installing them, so tests can log
+// through the subscriber with `tracing::subscriber::with_default`
+fn build_subscriber(config: &LoggerConfig) -> Result<impl tracing::Subscriber + Send + Sync> {
     // Create the log directory if it doesn't exist
     std::fs::create_dir_all(&config.directory).map_err(|e| {
         AppError::Config(ConfigError::FileRead(std::io::Error::new(
@@ -36,49 +69,173 @@ pub fn init_logging(config: LoggerConfig) -> Result<()> {
     })?;
 
     // Set up file appender
-    let file_appender =
-        RollingFileAppender::new(config.rotation, config.directory, config.file_name);
-
-    // Create a formatting layer for files
-    let file_layer = fmt::layer()
-        .with_file(true)
-        .with_line_number(true)
-        .with_thread_ids(true)
-        .with_thread_names(true)
-        .with_span_events(FmtSpan::FULL)
-        .with_writer(file_appender)
-        .with_target(true)
-        .with_level(true)
-        .with_ansi(false)
-        .with_filter(tracing::level_filters::LevelFilter::from_level(
-            config.level,
-        ));
-
-    // Create a formatting layer for stdout
-    let stdout_layer = fmt::layer()
-        .with_file(true)
-        .with_line_number(true)
-        .with_thread_ids(true)
-        .with_thread_names(true)
-        .with_target(true)
-        .with_level(true)
-        .with_ansi(true)
-        .with_filter(tracing::level_filters::LevelFilter::from_level(
-            config.level,
-        ));
-
-    // Combine both layers
-    let subscriber = Registry::default().with(file_layer).with(stdout_layer);
+    let file_appender = make_file_writer(config, &config.file_name)?;
 
-    // Set the subscriber as the default
-    tracing::subscriber::set_global_default(subscriber).map_err(|e| {
-        AppError::Config(ConfigError::InvalidValue(format!(
-            "Failed to set global subscriber: {}",
-            e
-        )))
-    })?;
+    // Create a formatting layer for files, either human-readable or one JSON object per line
+    let file_filter = tracing::level_filters::LevelFilter::from_level(config.level);
+    let file_layer = match config.format {
+        LogFormat::Pretty => fmt::layer()
+            .with_file(true)
+            .with_line_number(true)
+            .with_thread_ids(true)
+            .with_thread_names(true)
+            .with_span_events(FmtSpan::FULL)
+            .with_writer(file_appender)
+            .with_target(true)
+            .with_level(true)
+            .with_ansi(false)
+            .with_filter(file_filter)
+            .boxed(),
+        // Event fields (e.g. `error` and `kind` from `log_error!`) become top-level keys
+        LogFormat::Json => fmt::layer()
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_file(true)
+            .with_line_number(true)
+            .with_thread_ids(true)
+            .with_thread_names(true)
+            .with_span_events(FmtSpan::FULL)
+            .with_writer(file_appender)
+            .with_target(true)
+            .with_level(true)
+            .with_ansi(false)
+            .with_filter(file_filter)
+            .boxed(),
+    };
 
-    Ok(())
+    // Create a formatting layer for stdout unless it is turned off
+    let stdout_layer = config.stdout.then(|| {
+        fmt::layer()
+            .with_file(true)
+            .with_line_number(true)
+            .with_thread_ids(true)
+            .with_thread_names(true)
+            .with_target(true)
+            .with_level(true)
+            .with_ansi(true)
+            .with_filter(tracing::level_filters::LevelFilter::from_level(
+                config.level,
+            ))
+    });
+
+    // Optional layer that only captures warnings and errors for triage
+    let error_appender = config
+        .error_file_name
+        .as_deref()
+        .map(|error_file_name| make_file_writer(config, error_file_name))
+        .transpose()?;
+    let error_layer = error_appender.map(|error_appender| {
+        fmt::layer()
+            .with_file(true)
+            .with_line_number(true)
+            .with_thread_ids(true)
+            .with_thread_names(true)
+            .with_writer(error_appender)
+            .with_target(true)
+            .with_level(true)
+            .with_ansi(false)
+            .with_filter(tracing::level_filters::LevelFilter::WARN)
+    });
+
+    // Combine all layers
+    Ok(Registry::default()
+        .with(file_layer)
+        .with(stdout_layer)
+        .with(error_layer))
+}
+
+fn make_file_writer(config: &LoggerConfig, file_name: &str) -> Result<BoxMakeWriter> {
+    match config.max_size_bytes {
+        Some(max_size_bytes) => {
+            let writer = SizeRollingWriter::new(
+                Path::new(&config.directory).join(file_name),
+                max_size_bytes,
+                config.max_files,
+            )
+            .map_err(|e| AppError::Config(ConfigError::FileRead(e)))?;
+            Ok(BoxMakeWriter::new(Mutex::new(writer)))
+        }
+        None => Ok(BoxMakeWriter::new(RollingFileAppender::new(
+            config.rotation.clone(),
+            &config.directory,
+            file_name,
+        ))),
+    }
+}
+
+// File writer that rolls `name` to `name.1`, `name.2`, ... once it grows past `max_size` bytes
+pub struct SizeRollingWriter {
+    path: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    file: File,
+    written: u64,
+}
+
+impl SizeRollingWriter {
+    pub fn new(path: PathBuf, max_size: u64, max_files: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            max_size,
+            max_files,
+            file,
+            written,
+        })
+    }
+
+    fn archive_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    fn roll(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        if self.max_files == 0 {
+            std::fs::remove_file(&self.path)?;
+        } else {
+            // Shift older files up by one, dropping the oldest
+            let oldest = self.archive_path(self.max_files);
+            if oldest.exists() {
+                std::fs::remove_file(&oldest)?;
+            }
+            for index in (1..self.max_files).rev() {
+                let from = self.archive_path(index);
+                if from.exists() {
+                    std::fs::rename(&from, self.archive_path(index + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, self.archive_path(1))?;
+        }
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for SizeRollingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_size {
+            self.roll()?;
+        }
+
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
 }
 
 // Helper function to parse log level from string
@@ -147,3 +304,64 @@ macro_rules! log_trace {
         tracing::trace!($($arg)*);
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{build_subscriber, LoggerConfig, SizeRollingWriter};
+    use crate::utils;
+    use std::io::Write;
+
+    #[test]
+    fn rolls_past_max_size_and_keeps_max_files() {
+        let dir = utils::scratch_dir("logging-roll");
+        let path = dir.join("scraper.log");
+        let mut writer = SizeRollingWriter::new(path.clone(), 10, 2).unwrap();
+
+        writer.write_all(b"first 10b\n").unwrap();
+        writer.write_all(b"second\n").unwrap();
+        assert!(dir.join("scraper.log.1").exists());
+        assert_eq!(
+            std::fs::read_to_string(dir.join("scraper.log.1")).unwrap(),
+            "first 10b\n"
+        );
+
+        for line in ["third\n", "fourth\n", "fifth\n"] {
+            writer.write_all(line.as_bytes()).unwrap();
+        }
+        writer.flush().unwrap();
+
+        // Only the newest two archives survive next to the live file
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "fifth\n");
+        assert_eq!(
+            std::fs::read_to_string(dir.join("scraper.log.1")).unwrap(),
+            "fourth\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.join("scraper.log.2")).unwrap(),
+            "third\n"
+        );
+        assert!(!dir.join("scraper.log.3").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn file_layer_works_without_stdout() {
+        let dir = utils::scratch_dir("logging-no-stdout");
+        let config = LoggerConfig {
+            directory: dir.display().to_string(),
+            // Size-based rolling writes straight to `scraper.log`
+            max_size_bytes: Some(1024 * 1024),
+            stdout: false,
+            ..LoggerConfig::default()
+        };
+        let subscriber = build_subscriber(&config).unwrap();
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("file layer on its own");
+        });
+
+        let logged = std::fs::read_to_string(dir.join("scraper.log")).unwrap();
+        assert!(logged.contains("file layer on its own"), "{}", logged);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,185 @@
+use crate::proxy::ProxyManager;
+pub use crate::{log_error, log_info};
+use serde_json::json;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+// Counters of one run, updated from the download, processing and proxy code
+// paths. The run's `ProxyManager` owns them so every part of the run shares one set.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    pages_fetched: AtomicU64,
+    companies_extracted: AtomicU64,
+    proxy_successes: AtomicU64,
+    proxy_failures: AtomicU64,
+    // Unix time of the last saved page, 0 before the first one
+    last_page_at: AtomicU64,
+}
+
+impl Metrics {
+    pub fn page_fetched(&self) {
+        self.pages_fetched.fetch_add(1, Ordering::Relaxed);
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        self.last_page_at.store(now, Ordering::Relaxed);
+    }
+
+    pub fn pages_fetched(&self) -> u64 {
+        self.pages_fetched.load(Ordering::Relaxed)
+    }
+
+    pub fn companies_extracted(&self, count: usize) {
+        self.companies_extracted
+            .fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub fn proxy_success(&self) {
+        self.proxy_successes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn proxy_failure(&self) {
+        self.proxy_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // Prometheus text exposition format (version 0.0.4)
+    fn render(&self, working_proxies: usize) -> String {
+        let counters = [
+            (
+                "clutch_pages_fetched_total",
+                "Listing pages downloaded and saved",
+                &self.pages_fetched,
+            ),
+            (
+                "clutch_companies_extracted_total",
+                "Companies extracted from saved pages",
+                &self.companies_extracted,
+            ),
+            (
+                "clutch_proxy_successes_total",
+                "Requests that succeeded through a proxy",
+                &self.proxy_successes,
+            ),
+            (
+                "clutch_proxy_failures_total",
+                "Requests that failed through a proxy",
+                &self.proxy_failures,
+            ),
+        ];
+
+        let mut body = String::new();
+        for (name, help, counter) in counters {
+            let _ = writeln!(body, "# HELP {} {}", name, help);
+            let _ = writeln!(body, "# TYPE {} counter", name);
+            let _ = writeln!(body, "{} {}", name, counter.load(Ordering::Relaxed));
+        }
+        let _ = writeln!(
+            body,
+            "# HELP clutch_working_proxies Proxies currently in the working pool"
+        );
+        let _ = writeln!(body, "# TYPE clutch_working_proxies gauge");
+        let _ = writeln!(body, "clutch_working_proxies {}", working_proxies);
+        body
+    }
+
+    fn render_health(&self, working_proxies: usize) -> String {
+        let last_page_at = match self.last_page_at.load(Ordering::Relaxed) {
+            0 => None,
+            secs => time::OffsetDateTime::from_unix_timestamp(secs as i64)
+                .ok()
+                .and_then(|at| {
+                    at.format(&time::format_description::well_known::Rfc3339)
+                        .ok()
+                }),
+        };
+        json!({
+            "alive": true,
+            "last_page_at": last_page_at,
+            "pages_done": self.pages_fetched.load(Ordering::Relaxed),
+            "working_proxies": working_proxies,
+        })
+        .to_string()
+    }
+}
+
+// Routes the server answers; the rest get a 404
+#[derive(Debug, Clone, Copy)]
+pub struct Endpoints {
+    pub metrics: bool,
+    pub health: bool,
+}
+
+// Serves `GET /metrics` and `GET /health` on the given port until the task is aborted
+pub fn spawn_server(
+    port: u16,
+    endpoints: Endpoints,
+    proxy_manager: ProxyManager,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let listener = match TcpListener::bind(("0.0.0.0", port)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                log_error!("[metrics] Failed to bind port {}: {}", port, e);
+                return;
+            }
+        };
+        log_info!("[metrics] Serving {:?} on port {}", endpoints, port);
+
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    log_error!("[metrics] Failed to accept connection: {}", e);
+                    continue;
+                }
+            };
+            let proxy_manager = proxy_manager.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_connection(stream, endpoints, &proxy_manager).await {
+                    log_error!("[metrics] Failed to serve request: {}", e);
+                }
+            });
+        }
+    })
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    endpoints: Endpoints,
+    proxy_manager: &ProxyManager,
+) -> std::io::Result<()> {
+    // Only the request line matters, so one read is enough
+    let mut buffer = [0; 1024];
+    let read = stream.read(&mut buffer).await?;
+    let request = String::from_utf8_lossy(&buffer[..read]);
+
+    let response = if endpoints.metrics && request.starts_with("GET /metrics ") {
+        let body = proxy_manager
+            .metrics()
+            .render(proxy_manager.working_count().await);
+        ok_response("text/plain; version=0.0.4", &body)
+    } else if endpoints.health && request.starts_with("GET /health ") {
+        let body = proxy_manager
+            .metrics()
+            .render_health(proxy_manager.working_count().await);
+        ok_response("application/json", &body)
+    } else {
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+    };
+
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+fn ok_response(content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        content_type,
+        body.len(),
+        body
+    )
+}
@@ -0,0 +1,294 @@
+mod sink;
+#[cfg(test)]
+mod tests;
+
+use crate::config::{OutputConfig, OutputFormat, OutputMode};
+use crate::error::Result;
+use crate::scraper::CompanyData;
+use crate::utils;
+pub use crate::{log_error, log_info, log_warn};
+use serde::{Deserialize, Serialize};
+pub(crate) use sink::send_pages;
+pub use sink::{FileSink, WebhookSink};
+use std::path::{Path, PathBuf};
+
+// Companies extracted from one saved HTML page
+pub struct PageCompanies {
+    pub source: PathBuf,
+    pub companies: Vec<CompanyData>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrapeMetadata {
+    pub scraped_at: String,
+    pub base_url: String,
+    pub start_path: String,
+    pub total_companies: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScrapeResult<C> {
+    pub metadata: ScrapeMetadata,
+    pub companies: Vec<C>,
+}
+
+// Writes every configured output format from the same extracted data and
+// returns how many records each one actually holds on disk afterwards
+pub fn write_outputs(
+    config: &OutputConfig,
+    json_dir: &str,
+    metadata: &ScrapeMetadata,
+    pages: &[PageCompanies],
+) -> Result<Vec<(OutputFormat, usize)>> {
+    let mut written = Vec::new();
+    for format in config.formats() {
+        let count = match format {
+            OutputFormat::Json if config.mode == OutputMode::Aggregate => {
+                let path = Path::new(&config.json_file);
+                write_aggregate_json(path, metadata, pages)?;
+                count_aggregate_json(path)?
+            }
+            OutputFormat::Json => {
+                let paths = write_json_files(json_dir, pages)?;
+                count_json_files(&paths)?
+            }
+            OutputFormat::Csv => {
+                let path = Path::new(&config.csv_file);
+                write_csv(path, pages)?;
+                count_csv_records(path)?
+            }
+            OutputFormat::Jsonl => {
+                let path = Path::new(&config.jsonl_file);
+                write_jsonl(path, pages)?;
+                count_jsonl_records(path)?
+            }
+            OutputFormat::Both => unreachable!("`both` is expanded by OutputConfig::formats"),
+        };
+        written.push((format, count));
+    }
+    Ok(written)
+}
+
+// Incremental mode: diffs this run's companies against the snapshot at
+// `previous_file` and writes the diff to `changes_file`. Returns the pages
+// holding only the new and modified companies, which are all that needs
+// writing, and the full current set. The caller replaces the snapshot with it
+// once the changes were delivered, so a failed delivery is retried next run.
+pub fn keep_changed(
+    config: &OutputConfig,
+    previous_file: &Path,
+    metadata: &ScrapeMetadata,
+    mut pages: Vec<PageCompanies>,
+) -> Result<(Vec<PageCompanies>, ScrapeResult<CompanyData>)> {
+    let previous: Vec<CompanyData> = if previous_file.exists() {
+        let contents = std::fs::read_to_string(previous_file)?;
+        serde_json::from_str::<ScrapeResult<CompanyData>>(&contents)?.companies
+    } else {
+        log_info!(
+            "[output] No previous snapshot at {:?}, every company counts as new",
+            previous_file
+        );
+        Vec::new()
+    };
+
+    let current: Vec<CompanyData> = pages
+        .iter()
+        .flat_map(|page| page.companies.iter().cloned())
+        .collect();
+    let diff = utils::diff_companies(&previous, &current);
+    log_info!(
+        "[output] Changes since the previous run: {} added, {} modified, {} removed",
+        diff.added.len(),
+        diff.modified.len(),
+        diff.removed.len()
+    );
+    utils::save_json(&diff, &config.changes_file)?;
+
+    let snapshot = ScrapeResult {
+        metadata: metadata.clone(),
+        companies: current,
+    };
+
+    for page in pages.iter_mut() {
+        page.companies
+            .retain(|company| diff.is_changed(company.profile_url()));
+    }
+    Ok((pages, snapshot))
+}
+
+// Compares what each output holds against the number of extracted companies,
+// catching writes that failed without surfacing an error
+pub fn verify_counts(expected: usize, written: &[(OutputFormat, usize)]) -> bool {
+    let mut consistent = true;
+    for (format, count) in written {
+        if *count != expected {
+            log_warn!(
+                "[output] {:?} output holds {} records but {} companies were extracted",
+                format,
+                count,
+                expected
+            );
+            consistent = false;
+        }
+    }
+    consistent
+}
+
+// Counts the per-company files that still parse as a JSON object
+fn count_json_files(paths: &[PathBuf]) -> Result<usize> {
+    let mut count = 0;
+    for path in paths.iter().filter(|path| path.exists()) {
+        let contents = std::fs::read_to_string(path)?;
+        if serde_json::from_str::<serde_json::Value>(&contents).is_ok_and(|value| value.is_object())
+        {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+// Counts the lines that parse as a JSON object, so a truncated or corrupted
+// line is not mistaken for a record
+fn count_jsonl_records(path: &Path) -> Result<usize> {
+    if !path.exists() {
+        return Ok(0);
+    }
+    let contents = std::fs::read_to_string(path)?;
+    Ok(contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter(|line| {
+            serde_json::from_str::<serde_json::Value>(line).is_ok_and(|value| value.is_object())
+        })
+        .count())
+}
+
+// Counts the data rows that have as many fields as the header
+fn count_csv_records(path: &Path) -> Result<usize> {
+    if !path.exists() {
+        return Ok(0);
+    }
+    let contents = std::fs::read_to_string(path)?;
+    let counts = csv_field_counts(&contents);
+    let Some((header, rows)) = counts.split_first() else {
+        return Ok(0);
+    };
+    Ok(rows.iter().filter(|fields| *fields == header).count())
+}
+
+// Field count of every CSV record. Quoting follows RFC 4180, so line breaks
+// and commas inside a quoted field stay part of it; a record cut off inside
+// an open quote is dropped.
+fn csv_field_counts(contents: &str) -> Vec<usize> {
+    let mut counts = Vec::new();
+    let mut fields = 1;
+    let mut in_quotes = false;
+    let mut empty = true;
+    let mut chars = contents.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields += 1,
+            '\r' if !in_quotes => continue,
+            '\n' if !in_quotes => {
+                if !empty {
+                    counts.push(fields);
+                }
+                fields = 1;
+                empty = true;
+                continue;
+            }
+            _ => {}
+        }
+        empty = false;
+    }
+    if !empty && !in_quotes {
+        counts.push(fields);
+    }
+    counts
+}
+
+fn write_json_files(json_dir: &str, pages: &[PageCompanies]) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for page in pages {
+        let Some(file_name) = page.source.file_name() else {
+            continue;
+        };
+
+        for (index, company_data) in page.companies.iter().enumerate() {
+            let stem = file_name.to_string_lossy();
+            let stem = stem.trim_end_matches(".gz").trim_end_matches(".html");
+            let json_path =
+                Path::new(json_dir).join(format!("{}_company_{}.json", stem, index + 1));
+
+            utils::save_json(company_data, &json_path)?;
+            log_info!("[output] Saved company data to {:?}", json_path);
+            paths.push(json_path);
+        }
+    }
+    Ok(paths)
+}
+
+fn write_aggregate_json(
+    path: &Path,
+    metadata: &ScrapeMetadata,
+    pages: &[PageCompanies],
+) -> Result<()> {
+    let result = ScrapeResult {
+        metadata: metadata.clone(),
+        companies: pages.iter().flat_map(|page| &page.companies).collect(),
+    };
+
+    utils::save_json(&result, path)?;
+    log_info!(
+        "[output] Saved {} companies to {:?}",
+        result.companies.len(),
+        path
+    );
+    Ok(())
+}
+
+fn count_aggregate_json(path: &Path) -> Result<usize> {
+    if !path.exists() {
+        return Ok(0);
+    }
+    let contents = std::fs::read_to_string(path)?;
+    let result: ScrapeResult<serde_json::Value> = serde_json::from_str(&contents)?;
+    Ok(result.companies.len())
+}
+
+fn write_csv(path: &Path, pages: &[PageCompanies]) -> Result<()> {
+    // Start every run with a fresh file so the header is written once
+    utils::remove_file_if_exists(path)?;
+
+    for page in pages {
+        utils::save_csv(&page.companies, path)?;
+        log_info!(
+            "[output] Appended {} companies to {:?}",
+            page.companies.len(),
+            path
+        );
+    }
+    Ok(())
+}
+
+fn write_jsonl(path: &Path, pages: &[PageCompanies]) -> Result<()> {
+    utils::remove_file_if_exists(path)?;
+
+    for page in pages {
+        for company_data in &page.companies {
+            if let Err(e) = utils::append_jsonl(company_data, path) {
+                log_error!("[output] Failed to append company to {:?}: {}", path, e);
+            }
+        }
+        log_info!(
+            "[output] Appended {} companies to {:?}",
+            page.companies.len(),
+            path
+        );
+    }
+    Ok(())
+}
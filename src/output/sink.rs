@@ -0,0 +1,191 @@
+use super::{verify_counts, write_outputs, PageCompanies, ScrapeMetadata};
+use crate::client::Client;
+use crate::config::Config;
+use crate::error::{ConfigError, Result};
+use crate::scraper::CompanyData;
+use crate::utils;
+pub use crate::{log_error, log_info, log_warn};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+// Destination for extracted companies. `write` is called once per company and
+// `finish` once at the end, so sinks are free to buffer in between.
+pub(crate) trait OutputSink {
+    // Called before the companies extracted from one saved page
+    fn begin_page(&self, _source: &Path) {}
+
+    async fn write(&self, company: &CompanyData) -> Result<()>;
+
+    async fn finish(&self) -> Result<()>;
+}
+
+// Sends every company in page order, then finishes the sink
+pub(crate) async fn send_pages(sink: &impl OutputSink, pages: &[PageCompanies]) -> Result<()> {
+    for page in pages {
+        sink.begin_page(&page.source);
+        for company in &page.companies {
+            sink.write(company).await?;
+        }
+    }
+    sink.finish().await
+}
+
+// Writes the configured file formats (JSON, CSV, JSON Lines) once everything
+// has been collected, since aggregate JSON and CSV headers need the full set
+pub struct FileSink {
+    config: Config,
+    pages: Mutex<Vec<PageCompanies>>,
+}
+
+impl FileSink {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            config: config.clone(),
+            pages: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl OutputSink for FileSink {
+    fn begin_page(&self, source: &Path) {
+        self.pages.lock().unwrap().push(PageCompanies {
+            source: source.to_path_buf(),
+            companies: Vec::new(),
+        });
+    }
+
+    async fn write(&self, company: &CompanyData) -> Result<()> {
+        let mut pages = self.pages.lock().unwrap();
+        if pages.is_empty() {
+            pages.push(PageCompanies {
+                source: PathBuf::from("companies"),
+                companies: Vec::new(),
+            });
+        }
+        if let Some(page) = pages.last_mut() {
+            page.companies.push(company.clone());
+        }
+        Ok(())
+    }
+
+    async fn finish(&self) -> Result<()> {
+        let pages = std::mem::take(&mut *self.pages.lock().unwrap());
+        let total_companies = pages.iter().map(|page| page.companies.len()).sum();
+        let metadata = ScrapeMetadata {
+            scraped_at: utils::now_rfc3339(),
+            base_url: self.config.base_url.clone(),
+            start_path: self.config.start_path.clone(),
+            total_companies,
+        };
+        let written = write_outputs(
+            &self.config.output,
+            &self.config.storage.json_dir,
+            &metadata,
+            &pages,
+        )?;
+        verify_counts(total_companies, &written);
+        Ok(())
+    }
+}
+
+// POSTs companies to `output.webhook_url` as JSON arrays of up to
+// `output.webhook_batch_size`, retrying transient failures with backoff
+pub struct WebhookSink {
+    client: Client,
+    url: String,
+    batch_size: usize,
+    retries: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    pending: Mutex<Vec<CompanyData>>,
+    sent: AtomicUsize,
+}
+
+impl WebhookSink {
+    pub fn new(config: &Config) -> Result<Self> {
+        let url = config.output.webhook_url.clone().ok_or_else(|| {
+            ConfigError::MissingField("output.webhook_url is required by the webhook sink".into())
+        })?;
+        // Sent directly rather than through the scraping proxies
+        let client = Client::builder()
+            .base_url(&url)
+            .header("accept", "application/json")?
+            .cookie_store(false)
+            .timeout(Duration::from_secs(config.proxy_request_timeout))
+            .build()?;
+
+        Ok(Self {
+            client,
+            url,
+            batch_size: config.output.webhook_batch_size,
+            retries: config.output.webhook_retries,
+            base_delay: Duration::from_secs(config.retry_base_delay()),
+            max_delay: Duration::from_secs(config.retry.max_delay),
+            pending: Mutex::new(Vec::new()),
+            sent: AtomicUsize::new(0),
+        })
+    }
+
+    async fn post_batch(&self, batch: Vec<CompanyData>) -> Result<()> {
+        let mut attempt = 0;
+        loop {
+            match self.client.post_json("", &batch).await {
+                Ok(_) => {
+                    self.sent.fetch_add(batch.len(), Ordering::Relaxed);
+                    log_info!("[output] Posted {} companies to {}", batch.len(), self.url);
+                    return Ok(());
+                }
+                Err(e) if attempt < self.retries && e.is_transient() => {
+                    let wait = utils::backoff(attempt, self.base_delay, self.max_delay);
+                    attempt += 1;
+                    log_warn!(
+                        "[output] Webhook POST failed ({}), retry {}/{} in {:.1?}",
+                        e,
+                        attempt,
+                        self.retries,
+                        wait
+                    );
+                    tokio::time::sleep(wait).await;
+                }
+                Err(e) => {
+                    log_error!(
+                        "[output] Giving up on a batch of {} companies after {} attempts: {}",
+                        batch.len(),
+                        attempt + 1,
+                        e
+                    );
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+impl OutputSink for WebhookSink {
+    async fn write(&self, company: &CompanyData) -> Result<()> {
+        let batch = {
+            let mut pending = self.pending.lock().unwrap();
+            pending.push(company.clone());
+            if pending.len() < self.batch_size {
+                return Ok(());
+            }
+            std::mem::take(&mut *pending)
+        };
+        self.post_batch(batch).await
+    }
+
+    async fn finish(&self) -> Result<()> {
+        let batch = std::mem::take(&mut *self.pending.lock().unwrap());
+        if !batch.is_empty() {
+            self.post_batch(batch).await?;
+        }
+        log_info!(
+            "[output] Posted {} companies to {} in total",
+            self.sent.load(Ordering::Relaxed),
+            self.url
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+use super::*;
+use crate::scraper::extract_companies;
+
+fn fixture(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("reading {:?}: {}", path, e))
+}
+
+fn output_config(dir: &Path, formats: &[&str]) -> OutputConfig {
+    let mut config: OutputConfig = toml::from_str(&format!("formats = {:?}", formats)).unwrap();
+    config.json_file = dir.join("companies.json").display().to_string();
+    config.csv_file = dir.join("companies.csv").display().to_string();
+    config.jsonl_file = dir.join("companies.jsonl").display().to_string();
+    config
+}
+
+fn metadata(total_companies: usize) -> ScrapeMetadata {
+    ScrapeMetadata {
+        scraped_at: "2026-01-01T00:00:00Z".to_string(),
+        base_url: "http://clutch.test".to_string(),
+        start_path: "/developers".to_string(),
+        total_companies,
+    }
+}
+
+fn listing_pages() -> Vec<PageCompanies> {
+    let mut companies = extract_companies(&fixture("listing.html")).unwrap();
+    companies[0].description = Some("First line\nsecond, \"quoted\" line".to_string());
+    vec![PageCompanies {
+        source: PathBuf::from("page_1.html"),
+        companies,
+    }]
+}
+
+#[test]
+fn quoted_line_breaks_do_not_inflate_the_csv_count() {
+    let dir = utils::scratch_dir("output-csv");
+    let config = output_config(&dir, &["csv", "jsonl"]);
+    let pages = listing_pages();
+    let expected = pages[0].companies.len();
+
+    let written = write_outputs(&config, "", &metadata(expected), &pages).unwrap();
+
+    assert_eq!(
+        written,
+        vec![
+            (OutputFormat::Csv, expected),
+            (OutputFormat::Jsonl, expected)
+        ]
+    );
+    assert!(verify_counts(expected, &written));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn truncated_output_is_reported_as_a_mismatch() {
+    let dir = utils::scratch_dir("output-truncated");
+    let config = output_config(&dir, &["csv", "jsonl"]);
+    let pages = listing_pages();
+    let expected = pages[0].companies.len();
+    write_outputs(&config, "", &metadata(expected), &pages).unwrap();
+
+    // Cut both files off halfway through their last record
+    for file in [&config.csv_file, &config.jsonl_file] {
+        let contents = std::fs::read_to_string(file).unwrap();
+        let contents = contents.trim_end();
+        let last_record = contents.rfind('\n').unwrap() + 1;
+        let cut = last_record + (contents.len() - last_record) / 2;
+        std::fs::write(file, &contents[..cut]).unwrap();
+    }
+    let written = vec![
+        (
+            OutputFormat::Csv,
+            count_csv_records(Path::new(&config.csv_file)).unwrap(),
+        ),
+        (
+            OutputFormat::Jsonl,
+            count_jsonl_records(Path::new(&config.jsonl_file)).unwrap(),
+        ),
+    ];
+
+    assert_eq!(
+        written,
+        vec![
+            (OutputFormat::Csv, expected - 1),
+            (OutputFormat::Jsonl, expected - 1)
+        ]
+    );
+    assert!(!verify_counts(expected, &written));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn every_format_holds_the_same_companies() {
+    let dir = utils::scratch_dir("output-formats");
+    let mut config = output_config(&dir, &["json", "jsonl"]);
+    config.mode = OutputMode::Aggregate;
+    let pages = listing_pages();
+    let expected = pages[0].companies.len();
+
+    let written = write_outputs(&config, "", &metadata(expected), &pages).unwrap();
+    assert_eq!(
+        written,
+        vec![
+            (OutputFormat::Json, expected),
+            (OutputFormat::Jsonl, expected)
+        ]
+    );
+
+    let aggregate: ScrapeResult<serde_json::Value> =
+        serde_json::from_str(&std::fs::read_to_string(&config.json_file).unwrap()).unwrap();
+    let lines: Vec<serde_json::Value> = std::fs::read_to_string(&config.jsonl_file)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(aggregate.companies, lines);
+    assert_eq!(aggregate.metadata.total_companies, expected);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn keep_changed_leaves_the_snapshot_to_the_caller() {
+    let dir = utils::scratch_dir("output-incremental");
+    let mut config = output_config(&dir, &["json"]);
+    config.changes_file = dir.join("changes.json").display().to_string();
+    let previous_file = dir.join("previous.json");
+    let pages = listing_pages();
+    let expected = pages[0].companies.len();
+
+    let (changed, snapshot) =
+        keep_changed(&config, &previous_file, &metadata(expected), pages).unwrap();
+
+    // Everything is new, and the snapshot is only written once delivered
+    assert_eq!(changed[0].companies.len(), expected);
+    assert_eq!(snapshot.companies.len(), expected);
+    assert!(!previous_file.exists());
+    assert!(dir.join("changes.json").exists());
+    let _ = std::fs::remove_dir_all(&dir);
+}
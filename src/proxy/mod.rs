@@ -1,4 +1,8 @@
 mod manager;
+mod rate_limiter;
 mod stats;
+#[cfg(test)]
+mod tests;
 
 pub use manager::ProxyManager;
+pub use stats::{ProxyStats, ProxyStatsSnapshot, ValidationSummary};
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+// tokio's clock rather than std's so paused test time drives the refill too
+use tokio::time::Instant;
+
+// Token bucket shared by every worker so the combined request rate stays
+// under the limit no matter which proxy a request goes through
+#[derive(Clone)]
+pub struct RateLimiter {
+    state: Arc<Mutex<Bucket>>,
+    capacity: f64,
+    per_second: f64,
+}
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_minute: u32, burst: u32) -> Self {
+        let capacity = f64::from(burst.max(1));
+        Self {
+            state: Arc::new(Mutex::new(Bucket {
+                tokens: capacity,
+                last_refill: Instant::now(),
+            })),
+            capacity,
+            per_second: f64::from(requests_per_minute) / 60.0,
+        }
+    }
+
+    // Waits until a request may be sent
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut bucket = self.state.lock().await;
+                let now = Instant::now();
+                let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+                bucket.tokens = (bucket.tokens + elapsed * self.per_second).min(self.capacity);
+                bucket.last_refill = now;
+
+                if bucket.tokens >= 1.0 {
+                    bucket.tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - bucket.tokens) / self.per_second)
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
@@ -1,14 +1,34 @@
+use crate::error::Result;
+use crate::utils;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct ProxyStats {
     pub validation_status: Option<String>,
+    #[serde(default)]
+    pub country: Option<String>,
     pub total_requests: usize,
     pub successful_requests: usize,
     pub failed_requests: usize,
     pub status_codes: HashMap<u16, usize>,
     pub successful_urls: Vec<String>,
     pub failed_urls: Vec<(String, String)>, // (url, reason)
+    #[serde(default)]
+    pub total_latency: Duration,
+    #[serde(default)]
+    pub latency_samples: usize,
+    #[serde(default)]
+    pub min_latency: Option<Duration>,
+    #[serde(default)]
+    pub max_latency: Option<Duration>,
+    // Failed requests answered with a 4xx (blocks, throttling) and a 5xx
+    #[serde(default)]
+    pub client_errors: usize,
+    #[serde(default)]
+    pub server_errors: usize,
 }
 
 impl ProxyStats {
@@ -20,11 +40,38 @@ impl ProxyStats {
         self.validation_status = Some(status);
     }
 
-    pub fn record_success(&mut self, url: String, status_code: u16) {
+    pub fn record_success(&mut self, url: String, status_code: u16, elapsed: Duration) {
         self.total_requests += 1;
         self.successful_requests += 1;
         *self.status_codes.entry(status_code).or_default() += 1;
         self.successful_urls.push(url);
+
+        self.total_latency += elapsed;
+        self.latency_samples += 1;
+        self.min_latency = Some(self.min_latency.map_or(elapsed, |min| min.min(elapsed)));
+        self.max_latency = Some(self.max_latency.map_or(elapsed, |max| max.max(elapsed)));
+    }
+
+    pub fn average_latency(&self) -> Option<Duration> {
+        if self.latency_samples == 0 {
+            return None;
+        }
+        Some(self.total_latency / self.latency_samples as u32)
+    }
+
+    // Fraction of requests that succeeded, 0.0 when nothing was sent
+    pub fn success_rate(&self) -> f64 {
+        if self.total_requests == 0 {
+            return 0.0;
+        }
+        self.successful_requests as f64 / self.total_requests as f64
+    }
+
+    pub fn failure_rate(&self) -> f64 {
+        if self.total_requests == 0 {
+            return 0.0;
+        }
+        self.failed_requests as f64 / self.total_requests as f64
     }
 
     pub fn record_failure(&mut self, url: String, reason: String, status_code: Option<u16>) {
@@ -32,7 +79,165 @@ impl ProxyStats {
         self.failed_requests += 1;
         if let Some(code) = status_code {
             *self.status_codes.entry(code).or_default() += 1;
+            match code {
+                400..=499 => self.client_errors += 1,
+                500..=599 => self.server_errors += 1,
+                _ => {}
+            }
         }
         self.failed_urls.push((url, reason));
     }
+
+    // Adds the counters and latencies of a later period, e.g. to total the
+    // per-category snapshots of a multi-category run. What validation found
+    // is taken from `later` when it has it.
+    pub fn merge(&mut self, later: &ProxyStats) {
+        if later.validation_status.is_some() {
+            self.validation_status = later.validation_status.clone();
+        }
+        if later.country.is_some() {
+            self.country = later.country.clone();
+        }
+        self.total_requests += later.total_requests;
+        self.successful_requests += later.successful_requests;
+        self.failed_requests += later.failed_requests;
+        for (code, count) in &later.status_codes {
+            *self.status_codes.entry(*code).or_default() += count;
+        }
+        self.successful_urls
+            .extend(later.successful_urls.iter().cloned());
+        self.failed_urls.extend(later.failed_urls.iter().cloned());
+        self.total_latency += later.total_latency;
+        self.latency_samples += later.latency_samples;
+        self.min_latency = match (self.min_latency, later.min_latency) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.max_latency = match (self.max_latency, later.max_latency) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        self.client_errors += later.client_errors;
+        self.server_errors += later.server_errors;
+    }
+
+    // Copy without the per-request URL lists, which would otherwise grow with
+    // every run once persisted and reloaded
+    pub fn without_urls(&self) -> Self {
+        Self {
+            successful_urls: Vec::new(),
+            failed_urls: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    // Zeroes the request counters and latencies, keeping what validation found
+    pub fn reset(&mut self) {
+        *self = Self {
+            validation_status: self.validation_status.take(),
+            country: self.country.take(),
+            ..Self::default()
+        };
+    }
+}
+
+// One proxy's stats as taken by `ProxyManager::snapshot_and_reset_stats`
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyStatsSnapshot {
+    // Still in the working set when the snapshot was taken
+    pub active: bool,
+    pub stats: ProxyStats,
+}
+
+// Outcome of validating the proxy list at startup. Only covers proxies tested
+// in this run; timed out proxies are also counted as dead.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ValidationSummary {
+    pub total: usize,
+    pub working: usize,
+    pub dead: usize,
+    pub timed_out: usize,
+}
+
+impl std::fmt::Display for ValidationSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} of {} proxies working, {} dead ({} timed out)",
+            self.working, self.total, self.dead, self.timed_out
+        )
+    }
+}
+
+// Machine-readable version of the proxy performance report
+#[derive(Debug, Serialize)]
+pub struct ProxyReport {
+    pub generated_at: String,
+    pub working_count: usize,
+    pub dead_count: usize,
+    pub proxies: Vec<ProxyReportEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProxyReportEntry {
+    pub url: String,
+    pub active: bool,
+    pub success_rate: f64,
+    pub failure_rate: f64,
+    pub average_latency_ms: Option<u128>,
+    pub min_latency_ms: Option<u128>,
+    pub max_latency_ms: Option<u128>,
+    pub stats: ProxyStats,
+}
+
+impl ProxyReportEntry {
+    pub fn new(url: String, active: bool, stats: ProxyStats) -> Self {
+        Self {
+            url,
+            active,
+            success_rate: stats.success_rate(),
+            failure_rate: stats.failure_rate(),
+            average_latency_ms: stats.average_latency().map(|latency| latency.as_millis()),
+            min_latency_ms: stats.min_latency.map(|latency| latency.as_millis()),
+            max_latency_ms: stats.max_latency.map(|latency| latency.as_millis()),
+            stats,
+        }
+    }
+}
+
+impl ProxyReport {
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+// Snapshot of the proxy pool written at the end of a run and reloaded on startup
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PersistedProxies {
+    pub working: Vec<String>,
+    pub dead: Vec<String>,
+    pub failures: HashMap<String, u32>,
+    pub stats: HashMap<String, ProxyStats>,
+}
+
+impl PersistedProxies {
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let contents = std::fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&contents)?))
+    }
+
+    // Written atomically so a crash mid-write can't truncate the stats file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        utils::save_json(self, path)
+    }
 }
@@ -0,0 +1,149 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Retries allowed across the whole download within a sliding time window.
+// Clones share the same budget, so concurrent page workers draw from one pool
+// and a burst of failures stops retrying until older attempts age out.
+#[derive(Debug, Clone)]
+pub struct RetryBudget {
+    max_attempts: u32,
+    window: Duration,
+    attempts: Arc<Mutex<VecDeque<Instant>>>,
+}
+
+impl RetryBudget {
+    pub fn new(max_attempts: u32, window: Duration) -> Self {
+        Self {
+            max_attempts,
+            window,
+            attempts: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    pub fn can_retry(&self) -> bool {
+        self.can_retry_at(Instant::now())
+    }
+
+    pub fn record_attempt(&self) {
+        self.record_attempt_at(Instant::now());
+    }
+
+    // Retries recorded within the current window
+    pub fn used(&self) -> u32 {
+        self.used_at(Instant::now())
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    fn can_retry_at(&self, now: Instant) -> bool {
+        self.used_at(now) < self.max_attempts
+    }
+
+    fn record_attempt_at(&self, now: Instant) {
+        let mut attempts = self.attempts.lock().unwrap_or_else(|e| e.into_inner());
+        attempts.push_back(now);
+    }
+
+    fn used_at(&self, now: Instant) -> u32 {
+        let mut attempts = self.attempts.lock().unwrap_or_else(|e| e.into_inner());
+        while attempts
+            .front()
+            .is_some_and(|&attempt| now.saturating_duration_since(attempt) >= self.window)
+        {
+            attempts.pop_front();
+        }
+        attempts.len() as u32
+    }
+}
+
+// Trips once `threshold` page fetch attempts fail in a row across all pages
+// and proxies; any success resets it. A threshold of 0 never trips.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    threshold: usize,
+    consecutive_failures: Arc<AtomicUsize>,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: usize) -> Self {
+        Self {
+            threshold,
+            consecutive_failures: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn record_success(&self) {
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+    }
+
+    // Returns the failure streak when it has reached the threshold
+    pub fn record_failure(&self) -> Option<usize> {
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        (self.threshold > 0 && failures >= self.threshold).then_some(failures)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CircuitBreaker, RetryBudget};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn caps_attempts_within_the_window() {
+        let budget = RetryBudget::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+
+        assert!(budget.can_retry_at(start));
+        budget.record_attempt_at(start);
+        budget.record_attempt_at(start + Duration::from_secs(10));
+        assert!(!budget.can_retry_at(start + Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn attempts_age_out_of_the_window() {
+        let budget = RetryBudget::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+        budget.record_attempt_at(start);
+        budget.record_attempt_at(start + Duration::from_secs(10));
+
+        // The first attempt leaves the window, freeing one retry
+        assert!(budget.can_retry_at(start + Duration::from_secs(60)));
+        assert_eq!(budget.used_at(start + Duration::from_secs(60)), 1);
+        assert_eq!(budget.used_at(start + Duration::from_secs(70)), 0);
+    }
+
+    #[test]
+    fn clones_share_the_budget() {
+        let budget = RetryBudget::new(1, Duration::from_secs(60));
+        let worker = budget.clone();
+        worker.record_attempt();
+        assert!(!budget.can_retry());
+    }
+
+    #[test]
+    fn zero_budget_never_retries() {
+        let budget = RetryBudget::new(0, Duration::from_secs(60));
+        assert!(!budget.can_retry());
+    }
+
+    #[test]
+    fn breaker_trips_after_consecutive_failures_only() {
+        let breaker = CircuitBreaker::new(3);
+        assert_eq!(breaker.record_failure(), None);
+        assert_eq!(breaker.record_failure(), None);
+        breaker.record_success();
+        assert_eq!(breaker.record_failure(), None);
+        assert_eq!(breaker.record_failure(), None);
+        assert_eq!(breaker.record_failure(), Some(3));
+    }
+
+    #[test]
+    fn zero_threshold_never_trips() {
+        let breaker = CircuitBreaker::new(0);
+        assert!((0..10).all(|_| breaker.record_failure().is_none()));
+    }
+}
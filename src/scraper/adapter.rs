@@ -0,0 +1,36 @@
+use crate::error::Result;
+use crate::scraper::CompanyData;
+use scraper::{ElementRef, Html};
+
+// Everything that depends on the directory site being scraped. The scrapers
+// handle the site-independent parts (block page detection, extraction ratios,
+// logging) and ask the adapter for markup details.
+pub trait SiteAdapter: Clone {
+    // Base URL used until the caller sets one
+    fn default_base_url(&self) -> &str;
+
+    // Candidates for the element wrapping the listing, tried in order. A page
+    // matching none of them is a block or error page.
+    fn providers_list_selectors(&self) -> &[String];
+
+    // A single provider card inside the listing
+    fn providers_selector(&self) -> &str;
+
+    fn extract_company(&self, provider: &ElementRef, base_url: &str) -> Option<CompanyData>;
+
+    // Profile link of a card, used to report cards that failed to parse
+    fn profile_url(&self, provider: &ElementRef) -> Option<String>;
+
+    // 1-based number of the page shown in `document`
+    fn current_page(&self, document: &Html) -> Result<usize>;
+
+    fn total_pages(&self, document: &Html) -> Option<usize>;
+
+    // Absolute URL of the next page, `None` on the last page
+    fn next_page_url(
+        &self,
+        document: &Html,
+        base_url: &str,
+        current_path: &str,
+    ) -> Result<Option<String>>;
+}
@@ -0,0 +1,555 @@
+use super::adapter::SiteAdapter;
+use super::content::{Address, CompanyData, Rating, ReviewSnippet};
+use super::page::{page_number_of, page_path};
+use crate::config::SelectorConfig;
+use crate::error::{Result, ScraperError};
+pub use crate::{log_debug, log_info};
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use url::Url;
+
+// Adapter for clutch.co listings
+#[derive(Debug, Clone, Default)]
+pub struct ClutchAdapter {
+    selectors: SelectorConfig,
+}
+
+impl ClutchAdapter {
+    pub fn new(selectors: &SelectorConfig) -> Self {
+        Self {
+            selectors: selectors.clone(),
+        }
+    }
+}
+
+impl SiteAdapter for ClutchAdapter {
+    fn default_base_url(&self) -> &str {
+        "https://clutch.co"
+    }
+
+    fn providers_list_selectors(&self) -> &[String] {
+        &self.selectors.providers_list
+    }
+
+    fn providers_selector(&self) -> &str {
+        "li.provider-list-item"
+    }
+
+    fn extract_company(&self, provider: &ElementRef, base_url: &str) -> Option<CompanyData> {
+        let title = self.extract_title(provider)?;
+        let profile_url = absolute_url(base_url, &self.extract_profile_url(provider)?)?;
+        let min_engagement = self.extract_min_engagement(provider);
+        let min_project_size = self.extract_min_project_size(provider);
+        let hourly_rate = self.extract_hourly_rate(provider);
+        let employees = self.extract_employees(provider);
+
+        let missing: Vec<&str> = [
+            ("min_project_size", min_project_size.is_none()),
+            ("hourly_rate", hourly_rate.is_none()),
+            ("employees", employees.is_none()),
+        ]
+        .into_iter()
+        .filter_map(|(field, is_missing)| is_missing.then_some(field))
+        .collect();
+        if !missing.is_empty() {
+            log_debug!(
+                "[scraper] {} has no {} highlight",
+                title,
+                missing.join(", ")
+            );
+        }
+
+        let range =
+            |value: &Option<String>| value.as_deref().map_or((None, None), parse_numeric_range);
+        let hourly_rate_range = range(&hourly_rate);
+        let employees_range = range(&employees);
+        Some(CompanyData {
+            title,
+            reviews_url: self.extract_reviews_url(provider, &profile_url, base_url),
+            website_url: self.extract_website_url(provider, base_url),
+            description: self.extract_description(provider),
+            is_verified: self.is_verified(provider),
+            is_sponsored: self.is_sponsored(provider),
+            profile_url,
+            min_project_size_usd: range(&min_project_size).0,
+            min_project_size,
+            hourly_rate_min: hourly_rate_range.0,
+            hourly_rate_max: hourly_rate_range.1,
+            hourly_rate,
+            employees_min: employees_range.0,
+            employees_max: employees_range.1,
+            employees,
+            min_engagement_months: min_engagement.as_deref().and_then(parse_engagement_months),
+            min_engagement,
+            location: self.extract_location(provider),
+            services: self.extract_services(provider),
+            focus: self.extract_focus_areas(provider),
+            address: self.extract_address(provider)?,
+            rating: self.extract_rating(provider),
+            reviews: self.extract_reviews(provider),
+            awards: self.extract_awards(provider),
+        })
+    }
+
+    fn profile_url(&self, provider: &ElementRef) -> Option<String> {
+        self.extract_profile_url(provider)
+    }
+
+    fn current_page(&self, document: &Html) -> Result<usize> {
+        let selector = Selector::parse(".sg-pagination-v2-page-active")
+            .map_err(|e| ScraperError::SelectorError(e.to_string()))?;
+
+        if let Some(element) = document.select(&selector).next() {
+            element
+                .text()
+                .next()
+                .and_then(|t| t.trim().parse().ok())
+                .ok_or_else(|| {
+                    ScraperError::ParseError("Could not parse page number".into()).into()
+                })
+        } else {
+            Ok(1) // Default to page 1 if no pagination found
+        }
+    }
+
+    // The pager is truncated on long listings ("1 2 3 ... 87"), so the links'
+    // `page` queries are checked as well as the visible numbers
+    fn total_pages(&self, document: &Html) -> Option<usize> {
+        let selector = Selector::parse(".sg-pagination-v2-page").ok()?;
+        let visible_max = document
+            .select(&selector)
+            .filter_map(|el| {
+                el.text()
+                    .next()
+                    .and_then(|t| t.trim().parse::<usize>().ok())
+            })
+            .max();
+
+        let link_selector = Selector::parse(".sg-pagination-v2 a[href]").ok()?;
+        let linked_max = document
+            .select(&link_selector)
+            .filter_map(|a| a.value().attr("href"))
+            .map(page_number_of)
+            .max();
+
+        let total = visible_max.max(linked_max)?;
+        log_info!(
+            "[scraper] Found total pages: {} (from {})",
+            total,
+            if linked_max > visible_max {
+                "last pagination link"
+            } else {
+                "visible page numbers"
+            }
+        );
+        Some(total)
+    }
+
+    fn next_page_url(
+        &self,
+        document: &Html,
+        base_url: &str,
+        current_path: &str,
+    ) -> Result<Option<String>> {
+        let selector = Selector::parse(".sg-pagination-v2-next")
+            .map_err(|e| ScraperError::SelectorError(e.to_string()))?;
+
+        if let Some(next_element) = document.select(&selector).next() {
+            if next_element
+                .value()
+                .classes()
+                .any(|c| c == "sg-pagination-v2-disabled")
+            {
+                return Ok(None);
+            }
+
+            let base = Url::parse(base_url)
+                .map_err(|e| ScraperError::ParseError(format!("Invalid base URL: {}", e)))?;
+
+            // Prefer the real link; only derive it from the current path when missing
+            let next_url = match next_page_href(&next_element) {
+                Some(href) => base.join(href),
+                None => {
+                    let current_page = self.current_page(document)?;
+                    base.join(&page_path(current_path, current_page + 1))
+                }
+            }
+            .map_err(|e| ScraperError::ParseError(format!("Invalid next page URL: {}", e)))?;
+
+            Ok(Some(next_url.to_string()))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl ClutchAdapter {
+    fn extract_title(&self, provider: &ElementRef) -> Option<String> {
+        let selector = Selector::parse("a.provider__title-link").unwrap();
+        provider
+            .select(&selector)
+            .next()?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string()
+            .into()
+    }
+
+    fn extract_profile_url(&self, provider: &ElementRef) -> Option<String> {
+        let selector = Selector::parse("a.provider__title-link").unwrap();
+        provider
+            .select(&selector)
+            .next()?
+            .value()
+            .attr("href")?
+            .to_string()
+            .into()
+    }
+
+    fn extract_reviews_url(
+        &self,
+        provider: &ElementRef,
+        profile_url: &str,
+        base_url: &str,
+    ) -> Option<String> {
+        let selector = Selector::parse("a[href*='#reviews']").unwrap();
+        let href = provider
+            .select(&selector)
+            .next()
+            .and_then(|el| el.value().attr("href"))
+            .map(|href| href.to_string())
+            .unwrap_or_else(|| {
+                // Fall back to the profile page's reviews tab
+                let profile = profile_url.split('#').next().unwrap_or(profile_url);
+                format!("{}#reviews", profile)
+            });
+
+        absolute_url(base_url, &href)
+    }
+
+    fn extract_website_url(&self, provider: &ElementRef, base_url: &str) -> Option<String> {
+        let selector =
+            Selector::parse("a.website-link__item, a.provider__cta-link[rel~='nofollow']").unwrap();
+        let href = provider.select(&selector).next()?.value().attr("href")?;
+        let url = Url::parse(&absolute_url(base_url, href)?).ok()?;
+
+        // Clutch wraps outbound links in a redirect carrying the target in `u`
+        let destination = url
+            .query_pairs()
+            .find(|(key, _)| key == "u")
+            .map(|(_, value)| value.into_owned());
+
+        match destination {
+            Some(destination) if !destination.is_empty() => Some(destination),
+            _ => Some(url.to_string()),
+        }
+    }
+
+    fn extract_description(&self, provider: &ElementRef) -> Option<String> {
+        // The full text lives in the "more" paragraph; fall back to the truncated one
+        let selectors = [
+            "p.provider__description-text-more",
+            "p.provider__description-text-less",
+        ];
+
+        selectors.iter().find_map(|selector_str| {
+            let selector = Selector::parse(selector_str).unwrap();
+            let text = provider
+                .select(&selector)
+                .next()?
+                .text()
+                .collect::<String>();
+            Some(text.split_whitespace().collect::<Vec<_>>().join(" "))
+        })
+    }
+
+    // Ranking badge titles, taken from the badge text or, for image badges, the
+    // `title`/`alt` attribute. Empty when the card has none.
+    fn extract_awards(&self, provider: &ElementRef) -> Vec<String> {
+        let Ok(selector) = Selector::parse(&self.selectors.awards) else {
+            return Vec::new();
+        };
+        let image_selector = Selector::parse("img").unwrap();
+
+        let mut awards: Vec<String> = Vec::new();
+        for badge in provider.select(&selector) {
+            let text = badge.text().collect::<Vec<_>>().join(" ");
+            let mut award = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            if award.is_empty() {
+                award = std::iter::once(badge)
+                    .chain(badge.select(&image_selector))
+                    .find_map(|el| el.value().attr("title").or(el.value().attr("alt")))
+                    .map(|label| label.trim().to_string())
+                    .unwrap_or_default();
+            }
+            if !award.is_empty() && !awards.contains(&award) {
+                awards.push(award);
+            }
+        }
+        awards
+    }
+
+    // Highlighted review quotes on the card, empty when the card has none
+    fn extract_reviews(&self, provider: &ElementRef) -> Vec<ReviewSnippet> {
+        let Ok(container_selector) = Selector::parse(&self.selectors.review_container) else {
+            return Vec::new();
+        };
+        let find_text = |review: &ElementRef, selector: &str| -> Option<String> {
+            let selector = Selector::parse(selector).ok()?;
+            let text = review.select(&selector).next()?.text().collect::<String>();
+            let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            (!text.is_empty()).then_some(text)
+        };
+
+        provider
+            .select(&container_selector)
+            .filter_map(|review| {
+                Some(ReviewSnippet {
+                    quote: find_text(&review, &self.selectors.review_quote)?,
+                    reviewer: find_text(&review, &self.selectors.review_reviewer),
+                    role: find_text(&review, &self.selectors.review_role),
+                })
+            })
+            .collect()
+    }
+
+    fn is_verified(&self, provider: &ElementRef) -> bool {
+        let Ok(selector) = Selector::parse(&self.selectors.verified_badge) else {
+            return false;
+        };
+        provider.select(&selector).next().is_some()
+    }
+
+    fn is_sponsored(&self, provider: &ElementRef) -> bool {
+        provider
+            .value()
+            .classes()
+            .any(|class| self.selectors.sponsored_classes.iter().any(|c| c == class))
+    }
+
+    fn extract_min_project_size(&self, provider: &ElementRef) -> Option<String> {
+        let selector = Selector::parse("div.provider__highlights-item.min-project-size").unwrap();
+        provider
+            .select(&selector)
+            .next()?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string()
+            .into()
+    }
+
+    fn extract_hourly_rate(&self, provider: &ElementRef) -> Option<String> {
+        let selector = Selector::parse("div.provider__highlights-item.hourly-rate").unwrap();
+        provider
+            .select(&selector)
+            .next()?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string()
+            .into()
+    }
+
+    fn extract_employees(&self, provider: &ElementRef) -> Option<String> {
+        let selector = Selector::parse("div.provider__highlights-item.employees-count").unwrap();
+        provider
+            .select(&selector)
+            .next()?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string()
+            .into()
+    }
+
+    fn extract_min_engagement(&self, provider: &ElementRef) -> Option<String> {
+        let selector = Selector::parse("div.provider__highlights-item.min-engagement").unwrap();
+        let text = provider
+            .select(&selector)
+            .next()?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+
+        if text.is_empty() {
+            None
+        } else {
+            Some(text)
+        }
+    }
+
+    fn extract_location(&self, provider: &ElementRef) -> Option<String> {
+        let selector = Selector::parse("span.locality").unwrap();
+        provider
+            .select(&selector)
+            .next()?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string()
+            .into()
+    }
+
+    fn extract_services(&self, provider: &ElementRef) -> Vec<String> {
+        let selector =
+            Selector::parse(".provider__services--provided .provider__services-chart-item")
+                .unwrap();
+        provider
+            .select(&selector)
+            .filter_map(|el| el.value().attr("data-tooltip-content").map(tooltip_text))
+            .collect()
+    }
+
+    fn extract_focus_areas(&self, provider: &ElementRef) -> Vec<String> {
+        let selector =
+            Selector::parse(".provider__services--focus-areas .provider__services-chart-item")
+                .unwrap();
+        provider
+            .select(&selector)
+            .filter_map(|el| el.value().attr("data-tooltip-content").map(tooltip_text))
+            .collect()
+    }
+
+    // `None` only when the card has none of the address meta tags
+    fn extract_address(&self, provider: &ElementRef) -> Option<Address> {
+        let address = Address {
+            country: self.extract_meta_content(provider, "meta[itemprop='addressCountry']"),
+            locality: self.extract_meta_content(provider, "meta[itemprop='addressLocality']"),
+            region: self.extract_meta_content(provider, "meta[itemprop='addressRegion']"),
+            street: self.extract_meta_content(provider, "meta[itemprop='streetAddress']"),
+            postal_code: self.extract_meta_content(provider, "meta[itemprop='postalCode']"),
+            telephone: self.extract_meta_content(provider, "meta[itemprop='telephone']"),
+        };
+
+        let parts = [
+            &address.country,
+            &address.locality,
+            &address.region,
+            &address.street,
+            &address.postal_code,
+            &address.telephone,
+        ];
+        parts.iter().any(|part| part.is_some()).then_some(address)
+    }
+
+    fn extract_rating(&self, provider: &ElementRef) -> Option<Rating> {
+        Some(Rating {
+            average: self.extract_rating_number(provider),
+            review_count: self
+                .extract_meta_content_as_number(provider, "meta[itemprop='reviewCount']"),
+            best_rating: self
+                .extract_meta_content_as_number(provider, "meta[itemprop='bestRating']"),
+            worst_rating: self
+                .extract_meta_content_as_number(provider, "meta[itemprop='worstRating']"),
+            rating_value: self
+                .extract_meta_content_as_number(provider, "meta[itemprop='ratingValue']"),
+        })
+    }
+
+    fn extract_meta_content(&self, provider: &ElementRef, selector_str: &str) -> Option<String> {
+        let selector = Selector::parse(selector_str).unwrap();
+        provider
+            .select(&selector)
+            .next()?
+            .value()
+            .attr("content")?
+            .to_string()
+            .into()
+    }
+
+    fn extract_meta_content_as_number<T: std::str::FromStr>(
+        &self,
+        provider: &ElementRef,
+        selector_str: &str,
+    ) -> Option<T> {
+        self.extract_meta_content(provider, selector_str)?
+            .parse()
+            .ok()
+    }
+
+    fn extract_rating_number(&self, provider: &ElementRef) -> Option<f32> {
+        let selector = Selector::parse("span.sg-rating__number").unwrap();
+        provider
+            .select(&selector)
+            .next()?
+            .text()
+            .collect::<String>()
+            .trim()
+            .parse()
+            .ok()
+    }
+}
+
+fn next_page_href<'b>(next_element: &ElementRef<'b>) -> Option<&'b str> {
+    let anchor_selector = Selector::parse("a[href]").ok()?;
+    let href = next_element.value().attr("href").or_else(|| {
+        next_element
+            .select(&anchor_selector)
+            .next()
+            .and_then(|a| a.value().attr("href"))
+    })?;
+
+    let href = href.trim();
+    if href.is_empty() || href.starts_with('#') || href.starts_with("javascript:") {
+        None
+    } else {
+        Some(href)
+    }
+}
+
+// Resolves root-relative ("/profile/acme") and protocol-relative ("//clutch.co/...")
+// hrefs against `base_url`; absolute URLs are returned as written
+fn absolute_url(base_url: &str, href: &str) -> Option<String> {
+    let href = href.trim();
+    if Url::parse(href).is_ok() {
+        return Some(href.to_string());
+    }
+    let base = Url::parse(base_url).ok()?;
+    base.join(href).ok().map(|url| url.to_string())
+}
+
+// Turns text like "3 months", "6+ Months" or "1 year" into a number of months
+fn parse_engagement_months(text: &str) -> Option<u32> {
+    let re = Regex::new(r"(?i)(\d+)\s*\+?\s*(month|year)").unwrap();
+    let caps = re.captures(text)?;
+    let value: u32 = caps[1].parse().ok()?;
+
+    if caps[2].eq_ignore_ascii_case("year") {
+        value.checked_mul(12)
+    } else {
+        Some(value)
+    }
+}
+
+// Tooltip content is itself a small HTML fragment (e.g. "<i>50%</i> Web &amp; Mobile"),
+// so parse it to drop the markup and decode any entities left in the text
+fn tooltip_text(raw: &str) -> String {
+    Html::parse_fragment(raw)
+        .root_element()
+        .text()
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+// Parses Clutch range strings into (min, max) bounds:
+// "$50 - $99 / hr" -> (50, 99), "1,000+" -> (1000, None), "< $25 / hr" -> (None, 25),
+// "$10,000+" -> (10000, None), "Undisclosed" -> (None, None)
+pub(super) fn parse_numeric_range(text: &str) -> (Option<u32>, Option<u32>) {
+    let re = Regex::new(r"\d[\d,]*").unwrap();
+    let numbers: Vec<u32> = re
+        .find_iter(text)
+        .filter_map(|m| m.as_str().replace(',', "").parse().ok())
+        .collect();
+
+    match numbers.as_slice() {
+        [] => (None, None),
+        [value] if text.trim_start().starts_with('<') => (None, Some(*value)),
+        [value] if text.contains('+') => (Some(*value), None),
+        [value] => (Some(*value), Some(*value)),
+        [min, max, ..] => (Some(*min), Some(*max)),
+    }
+}
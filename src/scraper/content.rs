@@ -1,246 +1,457 @@
-pub use crate::{log_error, log_info};
+use super::adapter::SiteAdapter;
+use super::clutch::ClutchAdapter;
+use crate::block_page;
+use crate::config::{RedactMode, SelectorConfig};
+use crate::error::ScraperError;
+use crate::utils;
+pub use crate::{log_debug, log_error, log_info, log_warn};
 use scraper::{ElementRef, Html, Selector};
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
-#[derive(Debug, Serialize, Deserialize)]
+// Cards often omit one or two of the schema.org meta tags, so every part is optional
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Address {
-    country: String,
-    locality: String,
-    region: String,
-    street: String,
-    postal_code: String,
-    telephone: String,
+    pub(crate) country: Option<String>,
+    pub(crate) locality: Option<String>,
+    pub(crate) region: Option<String>,
+    pub(crate) street: Option<String>,
+    pub(crate) postal_code: Option<String>,
+    pub(crate) telephone: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Rating {
-    average: Option<f32>,
-    review_count: Option<i32>,
-    best_rating: Option<f32>,
-    worst_rating: Option<f32>,
-    rating_value: Option<f32>,
+    pub(crate) average: Option<f32>,
+    pub(crate) review_count: Option<i32>,
+    pub(crate) best_rating: Option<f32>,
+    pub(crate) worst_rating: Option<f32>,
+    pub(crate) rating_value: Option<f32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CompanyData {
-    title: String,
-    profile_url: String,
-    min_project_size: String,
-    hourly_rate: String,
-    employees: String,
-    location: Option<String>,
-    services: Vec<String>,
-    focus: Vec<String>,
-    address: Address,
-    rating: Option<Rating>,
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewSnippet {
+    pub(crate) quote: String,
+    pub(crate) reviewer: Option<String>,
+    pub(crate) role: Option<String>,
 }
 
-pub struct ContentScraper<'a> {
-    document: &'a Html,
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompanyData {
+    pub(crate) title: String,
+    pub(crate) profile_url: String,
+    pub(crate) reviews_url: Option<String>,
+    pub(crate) website_url: Option<String>,
+    pub(crate) description: Option<String>,
+    pub(crate) is_verified: bool,
+    pub(crate) is_sponsored: bool,
+    pub(crate) min_project_size: Option<String>,
+    pub(crate) min_project_size_usd: Option<u32>,
+    pub(crate) hourly_rate: Option<String>,
+    pub(crate) hourly_rate_min: Option<u32>,
+    pub(crate) hourly_rate_max: Option<u32>,
+    pub(crate) employees: Option<String>,
+    pub(crate) employees_min: Option<u32>,
+    pub(crate) employees_max: Option<u32>,
+    pub(crate) min_engagement: Option<String>,
+    pub(crate) min_engagement_months: Option<u32>,
+    pub(crate) location: Option<String>,
+    pub(crate) services: Vec<String>,
+    pub(crate) focus: Vec<String>,
+    pub(crate) address: Address,
+    pub(crate) rating: Option<Rating>,
+    #[serde(default)]
+    pub(crate) reviews: Vec<ReviewSnippet>,
+    #[serde(default)]
+    pub(crate) awards: Vec<String>,
 }
 
-impl<'a> ContentScraper<'a> {
-    pub(crate) fn new(document: &'a Html) -> Self {
-        Self { document }
+impl CompanyData {
+    pub const CSV_HEADERS: [&'static str; 33] = [
+        "title",
+        "profile_url",
+        "reviews_url",
+        "website_url",
+        "description",
+        "is_verified",
+        "is_sponsored",
+        "min_project_size",
+        "min_project_size_usd",
+        "hourly_rate",
+        "hourly_rate_min",
+        "hourly_rate_max",
+        "employees",
+        "employees_min",
+        "employees_max",
+        "min_engagement",
+        "min_engagement_months",
+        "location",
+        "services",
+        "focus",
+        "country",
+        "locality",
+        "region",
+        "street",
+        "postal_code",
+        "telephone",
+        "rating_average",
+        "rating_review_count",
+        "rating_best",
+        "rating_worst",
+        "rating_value",
+        "reviews",
+        "awards",
+    ];
+
+    pub fn profile_url(&self) -> &str {
+        &self.profile_url
     }
 
-    pub fn extract_companies_data(&self) -> Vec<CompanyData> {
-        let providers_list_selector =
-            Selector::parse("ul.providers__list#providers__list").unwrap();
+    // JSON Schema (draft 2020-12) of the serialized company. Written by hand, so
+    // a test checks its properties against the fields serde actually emits.
+    pub fn json_schema() -> Value {
+        let string = || json!({ "type": "string" });
+        let nullable = |kind: &str| json!({ "type": [kind, "null"] });
+        let strings = || json!({ "type": "array", "items": { "type": "string" } });
 
-        if let Some(providers_list) = self.document.select(&providers_list_selector).next() {
-            let provider_selector = Selector::parse("li.provider-list-item").unwrap();
-            let provider_count = providers_list.select(&provider_selector).count();
-            log_info!("Found {} provider items in the list", provider_count);
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "CompanyData",
+            "description": "A provider card from a Clutch listing page",
+            "type": "object",
+            "required": ["title", "profile_url", "is_verified", "is_sponsored", "address"],
+            "properties": {
+                "title": string(),
+                "profile_url": string(),
+                "reviews_url": nullable("string"),
+                "website_url": nullable("string"),
+                "description": nullable("string"),
+                "is_verified": { "type": "boolean" },
+                "is_sponsored": { "type": "boolean" },
+                "min_project_size": nullable("string"),
+                "min_project_size_usd": nullable("integer"),
+                "hourly_rate": nullable("string"),
+                "hourly_rate_min": nullable("integer"),
+                "hourly_rate_max": nullable("integer"),
+                "employees": nullable("string"),
+                "employees_min": nullable("integer"),
+                "employees_max": nullable("integer"),
+                "min_engagement": nullable("string"),
+                "min_engagement_months": nullable("integer"),
+                "location": nullable("string"),
+                "services": strings(),
+                "focus": strings(),
+                "address": {
+                    "type": "object",
+                    "properties": {
+                        "country": nullable("string"),
+                        "locality": nullable("string"),
+                        "region": nullable("string"),
+                        "street": nullable("string"),
+                        "postal_code": nullable("string"),
+                        "telephone": nullable("string"),
+                    },
+                },
+                "rating": {
+                    "type": ["object", "null"],
+                    "properties": {
+                        "average": nullable("number"),
+                        "review_count": nullable("integer"),
+                        "best_rating": nullable("number"),
+                        "worst_rating": nullable("number"),
+                        "rating_value": nullable("number"),
+                    },
+                },
+                "reviews": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["quote"],
+                        "properties": {
+                            "quote": string(),
+                            "reviewer": nullable("string"),
+                            "role": nullable("string"),
+                        },
+                    },
+                },
+                "awards": strings(),
+            },
+        })
+    }
 
-            let companies = providers_list
-                .select(&provider_selector)
-                .filter_map(|provider| {
-                    let result = self.extract_company_data(provider);
-                    if result.is_none() {
-                        log_error!("Failed to extract data for a company");
-                    }
-                    result
-                })
-                .collect::<Vec<_>>();
+    pub const REDACTABLE_FIELDS: [&'static str; 7] = [
+        "telephone",
+        "street",
+        "postal_code",
+        "locality",
+        "region",
+        "country",
+        "location",
+    ];
 
-            log_info!("Successfully extracted {} companies", companies.len());
-            companies
-        } else {
-            log_info!("Could not find the providers list");
-            Vec::new()
+    // Blanks or hashes the given fields in place before the company is written out
+    pub fn redact(&mut self, fields: &[String], mode: RedactMode) {
+        let apply = |value: &mut String| {
+            if value.is_empty() {
+                return;
+            }
+            *value = match mode {
+                RedactMode::Blank => String::new(),
+                RedactMode::Hash => utils::stable_hash(value),
+            };
+        };
+
+        for field in fields {
+            let value = match field.as_str() {
+                "telephone" => self.address.telephone.as_mut(),
+                "street" => self.address.street.as_mut(),
+                "postal_code" => self.address.postal_code.as_mut(),
+                "locality" => self.address.locality.as_mut(),
+                "region" => self.address.region.as_mut(),
+                "country" => self.address.country.as_mut(),
+                "location" => self.location.as_mut(),
+                _ => None,
+            };
+            if let Some(value) = value {
+                apply(value);
+            }
         }
     }
 
-    fn extract_company_data(&self, provider: ElementRef) -> Option<CompanyData> {
-        Some(CompanyData {
-            title: self.extract_title(&provider)?,
-            profile_url: self.extract_profile_url(&provider)?,
-            min_project_size: self.extract_min_project_size(&provider)?,
-            hourly_rate: self.extract_hourly_rate(&provider)?,
-            employees: self.extract_employees(&provider)?,
-            location: self.extract_location(&provider),
-            services: self.extract_services(&provider),
-            focus: self.extract_focus_areas(&provider),
-            address: self.extract_address(&provider)?,
-            rating: self.extract_rating(&provider),
-        })
+    // Flattens the company into a single CSV row matching `CSV_HEADERS`
+    pub fn to_csv_record(&self) -> Vec<String> {
+        fn opt<T: ToString>(value: Option<T>) -> String {
+            value.map(|v| v.to_string()).unwrap_or_default()
+        }
+
+        let rating = self.rating.as_ref();
+        vec![
+            self.title.clone(),
+            self.profile_url.clone(),
+            self.reviews_url.clone().unwrap_or_default(),
+            self.website_url.clone().unwrap_or_default(),
+            self.description.clone().unwrap_or_default(),
+            self.is_verified.to_string(),
+            self.is_sponsored.to_string(),
+            self.min_project_size.clone().unwrap_or_default(),
+            opt(self.min_project_size_usd),
+            self.hourly_rate.clone().unwrap_or_default(),
+            opt(self.hourly_rate_min),
+            opt(self.hourly_rate_max),
+            self.employees.clone().unwrap_or_default(),
+            opt(self.employees_min),
+            opt(self.employees_max),
+            self.min_engagement.clone().unwrap_or_default(),
+            opt(self.min_engagement_months),
+            self.location.clone().unwrap_or_default(),
+            self.services.join(";"),
+            self.focus.join(";"),
+            self.address.country.clone().unwrap_or_default(),
+            self.address.locality.clone().unwrap_or_default(),
+            self.address.region.clone().unwrap_or_default(),
+            self.address.street.clone().unwrap_or_default(),
+            self.address.postal_code.clone().unwrap_or_default(),
+            self.address.telephone.clone().unwrap_or_default(),
+            opt(rating.and_then(|r| r.average)),
+            opt(rating.and_then(|r| r.review_count)),
+            opt(rating.and_then(|r| r.best_rating)),
+            opt(rating.and_then(|r| r.worst_rating)),
+            opt(rating.and_then(|r| r.rating_value)),
+            self.reviews
+                .iter()
+                .map(|review| review.quote.as_str())
+                .collect::<Vec<_>>()
+                .join(" | "),
+            self.awards.join(" | "),
+        ]
     }
+}
 
-    fn extract_title(&self, provider: &ElementRef) -> Option<String> {
-        let selector = Selector::parse("a.provider__title-link").unwrap();
-        provider
-            .select(&selector)
-            .next()?
-            .text()
-            .collect::<String>()
-            .trim()
-            .to_string()
-            .into()
-    }
-
-    fn extract_profile_url(&self, provider: &ElementRef) -> Option<String> {
-        let selector = Selector::parse("a.provider__title-link").unwrap();
-        provider
-            .select(&selector)
-            .next()?
-            .value()
-            .attr("href")?
-            .to_string()
-            .into()
-    }
-
-    fn extract_min_project_size(&self, provider: &ElementRef) -> Option<String> {
-        let selector = Selector::parse("div.provider__highlights-item.min-project-size").unwrap();
-        provider
-            .select(&selector)
-            .next()?
-            .text()
-            .collect::<String>()
-            .trim()
-            .to_string()
-            .into()
+const SNIPPET_LENGTH: usize = 200;
+
+pub struct ContentScraper<'a, A: SiteAdapter = ClutchAdapter> {
+    document: &'a Html,
+    adapter: A,
+    base_url: String,
+    min_success_ratio: f64,
+}
+
+impl<'a> ContentScraper<'a, ClutchAdapter> {
+    pub fn with_selectors(mut self, selectors: &SelectorConfig) -> Self {
+        self.adapter = ClutchAdapter::new(selectors);
+        self
     }
+}
 
-    fn extract_hourly_rate(&self, provider: &ElementRef) -> Option<String> {
-        let selector = Selector::parse("div.provider__highlights-item.hourly-rate").unwrap();
-        provider
-            .select(&selector)
-            .next()?
-            .text()
-            .collect::<String>()
-            .trim()
-            .to_string()
-            .into()
+impl<'a, A: SiteAdapter> ContentScraper<'a, A> {
+    pub(crate) fn new(document: &'a Html, adapter: A) -> Self {
+        Self {
+            document,
+            base_url: adapter.default_base_url().to_string(),
+            adapter,
+            min_success_ratio: 0.0,
+        }
     }
 
-    fn extract_employees(&self, provider: &ElementRef) -> Option<String> {
-        let selector = Selector::parse("div.provider__highlights-item.employees-count").unwrap();
-        provider
-            .select(&selector)
-            .next()?
-            .text()
-            .collect::<String>()
-            .trim()
-            .to_string()
-            .into()
+    // Fail the page when fewer than this fraction of provider cards parse
+    pub fn with_min_success_ratio(mut self, min_success_ratio: f64) -> Self {
+        self.min_success_ratio = min_success_ratio;
+        self
     }
 
-    fn extract_location(&self, provider: &ElementRef) -> Option<String> {
-        let selector = Selector::parse("span.locality").unwrap();
-        provider
-            .select(&selector)
-            .next()?
-            .text()
-            .collect::<String>()
-            .trim()
-            .to_string()
-            .into()
-    }
-
-    fn extract_services(&self, provider: &ElementRef) -> Vec<String> {
-        let selector =
-            Selector::parse(".provider__services--provided .provider__services-chart-item")
-                .unwrap();
-        provider
-            .select(&selector)
-            .filter_map(|el| {
-                el.value()
-                    .attr("data-tooltip-content")
-                    .map(|s| s.replace("<i>", "").replace("</i>", ""))
-            })
-            .collect()
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
     }
 
-    fn extract_focus_areas(&self, provider: &ElementRef) -> Vec<String> {
-        let selector =
-            Selector::parse(".provider__services--focus-areas .provider__services-chart-item")
-                .unwrap();
-        provider
-            .select(&selector)
-            .filter_map(|el| {
-                el.value()
-                    .attr("data-tooltip-content")
-                    .map(|s| s.replace("<i>", "").replace("</i>", ""))
-            })
-            .collect()
+    // Fails when the providers list is missing so callers can tell a block or
+    // error page apart from a listing that is legitimately empty
+    pub fn extract_companies_data(&self) -> Result<Vec<CompanyData>, ScraperError> {
+        let mut companies = Vec::new();
+        self.for_each_company(|company| companies.push(company))?;
+        Ok(companies)
     }
 
-    fn extract_address(&self, provider: &ElementRef) -> Option<Address> {
-        Some(Address {
-            country: self.extract_meta_content(provider, "meta[itemprop='addressCountry']")?,
-            locality: self.extract_meta_content(provider, "meta[itemprop='addressLocality']")?,
-            region: self.extract_meta_content(provider, "meta[itemprop='addressRegion']")?,
-            street: self.extract_meta_content(provider, "meta[itemprop='streetAddress']")?,
-            postal_code: self.extract_meta_content(provider, "meta[itemprop='postalCode']")?,
-            telephone: self.extract_meta_content(provider, "meta[itemprop='telephone']")?,
-        })
+    pub fn check_providers_list(&self) -> Result<(), ScraperError> {
+        match self.providers_list()? {
+            Some(_) => Ok(()),
+            None => Err(self.missing_providers_list_error()),
+        }
+    }
+
+    fn missing_providers_list_error(&self) -> ScraperError {
+        let snippet = self.snippet();
+        match self.block_page_reason() {
+            Some(reason) => ScraperError::BlockedPage { reason, snippet },
+            None => {
+                ScraperError::ParseError(format!("Could not find the providers list: {}", snippet))
+            }
+        }
     }
 
-    fn extract_rating(&self, provider: &ElementRef) -> Option<Rating> {
-        Some(Rating {
-            average: self.extract_rating_number(provider),
-            review_count: self
-                .extract_meta_content_as_number(provider, "meta[itemprop='reviewCount']"),
-            best_rating: self
-                .extract_meta_content_as_number(provider, "meta[itemprop='bestRating']"),
-            worst_rating: self
-                .extract_meta_content_as_number(provider, "meta[itemprop='worstRating']"),
-            rating_value: self
-                .extract_meta_content_as_number(provider, "meta[itemprop='ratingValue']"),
+    fn block_page_reason(&self) -> Option<String> {
+        let title_selector = Selector::parse("title").unwrap();
+        let title = self
+            .document
+            .select(&title_selector)
+            .next()
+            .map(|title| title.text().collect::<String>())
+            .unwrap_or_default();
+        if let Some(marker) = block_page::TITLES
+            .iter()
+            .find(|marker| title.contains(*marker))
+        {
+            return Some(format!("title \"{}\"", marker));
+        }
+
+        block_page::SELECTORS.iter().find_map(|selector| {
+            let parsed = Selector::parse(selector).ok()?;
+            self.document
+                .select(&parsed)
+                .next()
+                .map(|_| format!("element {}", selector))
         })
     }
 
-    fn extract_meta_content(&self, provider: &ElementRef, selector_str: &str) -> Option<String> {
-        let selector = Selector::parse(selector_str).unwrap();
-        provider
-            .select(&selector)
-            .next()?
-            .value()
-            .attr("content")?
-            .to_string()
-            .into()
-    }
-
-    fn extract_meta_content_as_number<T: std::str::FromStr>(
-        &self,
-        provider: &ElementRef,
-        selector_str: &str,
-    ) -> Option<T> {
-        self.extract_meta_content(provider, selector_str)?
-            .parse()
-            .ok()
-    }
-
-    fn extract_rating_number(&self, provider: &ElementRef) -> Option<f32> {
-        let selector = Selector::parse("span.sg-rating__number").unwrap();
-        provider
-            .select(&selector)
-            .next()?
+    fn snippet(&self) -> String {
+        let text = self
+            .document
+            .root_element()
             .text()
-            .collect::<String>()
-            .trim()
-            .parse()
-            .ok()
+            .collect::<Vec<_>>()
+            .join(" ");
+        text.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .take(SNIPPET_LENGTH)
+            .collect()
+    }
+
+    pub fn provider_count(&self) -> usize {
+        let (Ok(Some(providers_list)), Ok(provider_selector)) =
+            (self.providers_list(), self.provider_selector())
+        else {
+            return 0;
+        };
+
+        providers_list.select(&provider_selector).count()
+    }
+
+    // Extracts companies one at a time and hands each to `f`, so callers can
+    // process them without collecting the whole page first
+    pub fn for_each_company(&self, mut f: impl FnMut(CompanyData)) -> Result<(), ScraperError> {
+        if let Some(providers_list) = self.providers_list()? {
+            let provider_selector = self.provider_selector()?;
+            let provider_count = providers_list.select(&provider_selector).count();
+            log_info!("Found {} provider items in the list", provider_count);
+
+            let mut extracted = 0;
+            let mut failed_urls = Vec::new();
+            for provider in providers_list.select(&provider_selector) {
+                match self.adapter.extract_company(&provider, &self.base_url) {
+                    Some(company) => {
+                        extracted += 1;
+                        f(company);
+                    }
+                    None => {
+                        log_error!("Failed to extract data for a company");
+                        failed_urls.push(
+                            self.adapter
+                                .profile_url(&provider)
+                                .unwrap_or_else(|| "<no profile url>".to_string()),
+                        );
+                    }
+                }
+            }
+
+            log_info!("Successfully extracted {} companies", extracted);
+
+            // A gap between cards found and cards parsed usually means the markup changed
+            if extracted < provider_count {
+                log_warn!(
+                    "Extracted {} of {} provider cards; failed: {}",
+                    extracted,
+                    provider_count,
+                    failed_urls.join(", ")
+                );
+                if (extracted as f64) < provider_count as f64 * self.min_success_ratio {
+                    return Err(ScraperError::LowExtractionRatio {
+                        extracted,
+                        found: provider_count,
+                    });
+                }
+            }
+            Ok(())
+        } else {
+            log_info!("Could not find the providers list");
+            Err(self.missing_providers_list_error())
+        }
+    }
+
+    // First listing container matched by the adapter's candidate selectors
+    fn providers_list(&self) -> Result<Option<ElementRef<'a>>, ScraperError> {
+        for (index, candidate) in self.adapter.providers_list_selectors().iter().enumerate() {
+            let selector = Selector::parse(candidate)
+                .map_err(|e| ScraperError::SelectorError(e.to_string()))?;
+            if let Some(providers_list) = self.document.select(&selector).next() {
+                if index == 0 {
+                    log_debug!("[scraper] Providers list matched {}", candidate);
+                } else {
+                    log_info!(
+                        "[scraper] Providers list matched fallback selector {} (#{})",
+                        candidate,
+                        index + 1
+                    );
+                }
+                return Ok(Some(providers_list));
+            }
+        }
+        Ok(None)
+    }
+
+    fn provider_selector(&self) -> Result<Selector, ScraperError> {
+        Selector::parse(self.adapter.providers_selector())
+            .map_err(|e| ScraperError::SelectorError(e.to_string()))
     }
 }
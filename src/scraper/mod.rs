@@ -1,27 +1,49 @@
+mod adapter;
+mod clutch;
 mod content;
 mod page;
+#[cfg(test)]
+mod tests;
 
-pub use content::ContentScraper;
-pub use page::PageScraper;
+pub use adapter::SiteAdapter;
+pub use clutch::ClutchAdapter;
+pub use content::{CompanyData, ContentScraper};
+pub use page::{page_number_of, page_path, PageInfo, PageScraper};
 
+use crate::error::Result;
 use scraper::Html;
 
-pub struct Scraper {
+pub struct Scraper<A: SiteAdapter = ClutchAdapter> {
     document: Html,
+    adapter: A,
 }
 
 impl Scraper {
     pub fn new(html: &str) -> Self {
+        Self::with_adapter(html, ClutchAdapter::default())
+    }
+}
+
+impl<A: SiteAdapter> Scraper<A> {
+    pub fn with_adapter(html: &str, adapter: A) -> Self {
         Self {
             document: Html::parse_document(html),
+            adapter,
         }
     }
 
-    pub fn page(&self) -> PageScraper {
-        PageScraper::new(&self.document)
+    pub fn page(&self) -> PageScraper<A> {
+        PageScraper::new(&self.document, self.adapter.clone())
     }
 
-    pub fn content(&self) -> ContentScraper {
-        ContentScraper::new(&self.document)
+    pub fn content(&self) -> ContentScraper<A> {
+        ContentScraper::new(&self.document, self.adapter.clone())
     }
 }
+
+// Extracts the companies on a Clutch listing page fetched elsewhere, with the
+// default selectors. Cards missing optional fields still come through; a page
+// without the providers list fails with `BlockedPage` or `ParseError`.
+pub fn extract_companies(html: &str) -> Result<Vec<CompanyData>> {
+    Ok(Scraper::new(html).content().extract_companies_data()?)
+}
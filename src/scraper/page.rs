@@ -1,24 +1,35 @@
-use crate::error::{Result, ScraperError};
+use super::adapter::SiteAdapter;
+use super::clutch::ClutchAdapter;
+use crate::error::Result;
 pub use crate::log_info;
+use regex::Regex;
 use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use url::Url;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PageInfo {
     pub current_page: usize,
     pub next_url: Option<String>,
     pub total_pages: Option<usize>,
+    pub results_on_page: usize,
+    pub total_results: Option<usize>,
 }
 
-pub struct PageScraper<'a> {
+pub struct PageScraper<'a, A: SiteAdapter = ClutchAdapter> {
     document: &'a Html,
+    adapter: A,
     base_url: String,
+    current_path: String,
 }
 
-impl<'a> PageScraper<'a> {
-    pub(crate) fn new(document: &'a Html) -> Self {
+impl<'a, A: SiteAdapter> PageScraper<'a, A> {
+    pub(crate) fn new(document: &'a Html, adapter: A) -> Self {
         Self {
             document,
-            base_url: "https://clutch.co".to_string(),
+            base_url: adapter.default_base_url().to_string(),
+            adapter,
+            current_path: "/".to_string(),
         }
     }
 
@@ -27,81 +38,127 @@ impl<'a> PageScraper<'a> {
         self
     }
 
+    pub fn with_current_path(mut self, current_path: impl Into<String>) -> Self {
+        self.current_path = current_path.into();
+        self
+    }
+
     pub fn analyze(&self) -> Result<PageInfo> {
-        let current_page = self.get_current_page()?;
-        let next_url = self.get_next_page_url()?;
-        let total_pages = self.get_total_pages();
+        let current_page = self.adapter.current_page(self.document)?;
+        let next_url =
+            self.adapter
+                .next_page_url(self.document, &self.base_url, &self.current_path)?;
+        let results_on_page = self.get_results_on_page();
+        let total_results = self.get_total_results();
+        let total_pages =
+            self.estimate_total_pages(results_on_page, total_results, next_url.is_some());
 
         // Log pagination information
         log_info!(
-            "[scraper] Page Analysis - Current: {}, Total: {}, Next: {}",
+            "[scraper] Page Analysis - Current: {}, Total: {}, Next: {}, Results: {}/{}",
             current_page,
             total_pages.unwrap_or(0),
-            next_url.as_deref().unwrap_or("None")
+            next_url.as_deref().unwrap_or("None"),
+            results_on_page,
+            total_results.map_or("unknown".to_string(), |t| t.to_string())
         );
 
         Ok(PageInfo {
             current_page,
             next_url,
             total_pages,
+            results_on_page,
+            total_results,
         })
     }
 
-    fn get_current_page(&self) -> Result<usize> {
-        let selector = Selector::parse(".sg-pagination-v2-page-active")
-            .map_err(|e| ScraperError::SelectorError(e.to_string()))?;
-
-        if let Some(element) = self.document.select(&selector).next() {
-            element
-                .text()
-                .next()
-                .and_then(|t| t.trim().parse().ok())
-                .ok_or_else(|| {
-                    ScraperError::ParseError("Could not parse page number".into()).into()
-                })
-        } else {
-            Ok(1) // Default to page 1 if no pagination found
-        }
-    }
+    // Takes the larger of the pager's count and "X results" divided by the cards
+    // on this page. The latter is only trusted on pages that have a next page,
+    // since the last page is usually short.
+    fn estimate_total_pages(
+        &self,
+        results_on_page: usize,
+        total_results: Option<usize>,
+        has_next: bool,
+    ) -> Option<usize> {
+        let from_pager = self.adapter.total_pages(self.document);
+        let from_results = total_results
+            .filter(|_| has_next && results_on_page > 0)
+            .map(|total| total.div_ceil(results_on_page));
 
-    fn get_next_page_url(&self) -> Result<Option<String>> {
-        let selector = Selector::parse(".sg-pagination-v2-next")
-            .map_err(|e| ScraperError::SelectorError(e.to_string()))?;
-
-        if let Some(next_element) = self.document.select(&selector).next() {
-            if next_element
-                .value()
-                .classes()
-                .any(|c| c == "sg-pagination-v2-disabled")
-            {
-                return Ok(None);
+        let total_pages = from_pager.max(from_results)?;
+        log_info!(
+            "[scraper] Using {} total pages from the {}",
+            total_pages,
+            if from_results > from_pager {
+                "results count"
+            } else {
+                "pagination"
             }
+        );
+        Some(total_pages)
+    }
 
-            let current_page = self.get_current_page()?;
-            let next_url = format!("/developers/rust?page={}", current_page);
-            Ok(Some(format!("{}{}", self.base_url, next_url)))
-        } else {
-            Ok(None)
-        }
+    fn get_results_on_page(&self) -> usize {
+        let Ok(selector) = Selector::parse(self.adapter.providers_selector()) else {
+            return 0;
+        };
+        self.document.select(&selector).count()
     }
 
-    fn get_total_pages(&self) -> Option<usize> {
-        let selector = Selector::parse(".sg-pagination-v2-page").ok()?;
-        let max_page = self
-            .document
-            .select(&selector)
-            .map(|el| {
-                el.text()
-                    .next()
-                    .and_then(|t| t.trim().parse::<usize>().ok())
-            })
-            .flatten()
-            .max();
-
-        if let Some(total) = max_page {
-            log_info!("[scraper] Found total pages: {}", total);
+    // Parses the "1,234 Companies" style header, tolerating commas and the
+    // words around the number
+    fn get_total_results(&self) -> Option<usize> {
+        let selector =
+            Selector::parse(".providers__count, .facets_title, h1, h2, .sg-heading").ok()?;
+        let re = Regex::new(r"(?i)(\d[\d,]*)\+?\s+(?:companies|firms|agencies|providers|results)")
+            .ok()?;
+
+        self.document.select(&selector).find_map(|el| {
+            let text = el.text().collect::<String>();
+            let caps = re.captures(&text)?;
+            caps[1].replace(',', "").parse().ok()
+        })
+    }
+}
+
+// 1-based page number a listing path or URL points at, the inverse of `page_path`
+pub fn page_number_of(path_or_url: &str) -> usize {
+    Url::parse("https://clutch.co")
+        .and_then(|base| base.join(path_or_url))
+        .ok()
+        .and_then(|url| {
+            url.query_pairs()
+                .find(|(key, _)| key == "page")
+                .and_then(|(_, value)| value.parse::<usize>().ok())
+        })
+        .map_or(1, |page| page + 1)
+}
+
+// Builds the listing path for a 1-based page number, replacing any existing
+// `page` query. Clutch's `page` parameter is zero-based, so page 1 has none.
+pub fn page_path(path: &str, page_number: usize) -> String {
+    let Ok(mut url) = Url::parse("https://clutch.co").and_then(|base| base.join(path)) else {
+        return path.to_string();
+    };
+
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, _)| key != "page")
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+
+    url.set_query(None);
+    if !pairs.is_empty() || page_number > 1 {
+        let mut query = url.query_pairs_mut();
+        query.extend_pairs(pairs);
+        if page_number > 1 {
+            query.append_pair("page", &(page_number - 1).to_string());
         }
+    }
 
-        max_page
+    match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
     }
 }
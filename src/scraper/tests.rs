@@ -0,0 +1,408 @@
+use super::clutch::parse_numeric_range;
+use super::{extract_companies, page_number_of, page_path, CompanyData, Scraper};
+use crate::config::RedactMode;
+use crate::error::{AppError, ScraperError};
+use crate::utils;
+use std::path::Path;
+
+fn fixture(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("reading {:?}: {}", path, e))
+}
+
+#[test]
+fn extracts_known_company_from_listing() {
+    let html = fixture("listing.html");
+    let companies = Scraper::new(&html)
+        .content()
+        .extract_companies_data()
+        .unwrap();
+    assert_eq!(companies.len(), 2);
+
+    let company = &companies[0];
+    assert_eq!(company.title, "Ferrous Labs");
+    assert_eq!(
+        company.profile_url,
+        "https://clutch.co/profile/ferrous-labs"
+    );
+    assert_eq!(company.hourly_rate.as_deref(), Some("$100 - $149 / hr"));
+    assert_eq!(company.hourly_rate_min, Some(100));
+    assert_eq!(company.hourly_rate_max, Some(149));
+    assert_eq!(company.employees_min, Some(50));
+    assert_eq!(company.focus, ["100% Rust"]);
+    assert_eq!(
+        company.website_url.as_deref(),
+        Some("https://ferrous-labs.example/")
+    );
+    assert_eq!(
+        company.description.as_deref(),
+        Some("Ferrous Labs builds fast, reliable systems software in Rust.")
+    );
+    assert!(company.is_verified);
+    assert!(!company.is_sponsored);
+
+    let address = &company.address;
+    assert_eq!(address.country.as_deref(), Some("DE"));
+    assert_eq!(address.locality.as_deref(), Some("Berlin"));
+    assert_eq!(address.street.as_deref(), Some("Rustweg 1"));
+    assert_eq!(address.postal_code.as_deref(), Some("10115"));
+    assert_eq!(address.telephone.as_deref(), Some("+49 30 1234567"));
+}
+
+#[test]
+fn extracts_award_badges() {
+    let html = fixture("award_badges.html");
+    let companies = Scraper::new(&html)
+        .content()
+        .extract_companies_data()
+        .unwrap();
+    assert_eq!(companies.len(), 2);
+
+    // Text badges lose their inner markup, image badges use their title
+    assert_eq!(
+        companies[0].awards,
+        ["Top Rust Developers 2024", "Top Blockchain Companies 2024"]
+    );
+    assert!(companies[1].awards.is_empty());
+
+    // The listing's second badge is an image with only an alt text
+    let listing = extract_companies(&fixture("listing.html")).unwrap();
+    assert_eq!(
+        listing[0].awards,
+        [
+            "Top Rust Developers 2024",
+            "Top Software Developers Germany 2024"
+        ]
+    );
+}
+
+#[test]
+fn reviews_url_comes_from_the_card_or_the_profile() {
+    // A relative reviews anchor on the card is made absolute
+    let listing = fixture("listing.html").replace(
+        r#"<a href="https://clutch.co/profile/ferrous-labs#reviews">"#,
+        r#"<a href="/profile/ferrous-labs?sort=newest#reviews">"#,
+    );
+    let companies = extract_companies(&listing).unwrap();
+    assert_eq!(
+        companies[0].reviews_url.as_deref(),
+        Some("https://clutch.co/profile/ferrous-labs?sort=newest#reviews")
+    );
+
+    // Without an anchor the profile's reviews tab is used
+    assert_eq!(
+        companies[1].reviews_url.as_deref(),
+        Some("https://clutch.co/profile/oxide-works#reviews")
+    );
+}
+
+#[test]
+fn decodes_entities_in_service_tooltips() {
+    let companies = extract_companies(&fixture("listing.html")).unwrap();
+    assert_eq!(
+        companies[0].services,
+        [
+            "60% Custom Software Development",
+            "40% Web & Mobile Development"
+        ]
+    );
+
+    // Clutch escapes the tooltip fragment once more inside the attribute, so
+    // its value still holds entities after the page itself is parsed
+    let listing = fixture("listing.html")
+        .replace(
+            "<i>60%</i> Custom Software Development",
+            "<i>60%</i> Developers&amp;#39; Tools",
+        )
+        .replace("Web &amp; Mobile", "Web &amp;amp; Mobile");
+    let companies = extract_companies(&listing).unwrap();
+    assert_eq!(
+        companies[0].services,
+        ["60% Developers' Tools", "40% Web & Mobile Development"]
+    );
+}
+
+#[test]
+fn extracts_min_engagement_when_shown() {
+    let listing = fixture("listing.html");
+    let companies = extract_companies(&listing).unwrap();
+    assert_eq!(companies[0].min_engagement.as_deref(), Some("6+ Months"));
+    assert_eq!(companies[0].min_engagement_months, Some(6));
+    // The second card has no engagement highlight
+    assert_eq!(companies[1].min_engagement, None);
+    assert_eq!(companies[1].min_engagement_months, None);
+
+    let in_years = listing.replace("6+ Months", "1+ Year");
+    let companies = extract_companies(&in_years).unwrap();
+    assert_eq!(companies[0].min_engagement.as_deref(), Some("1+ Year"));
+    assert_eq!(companies[0].min_engagement_months, Some(12));
+}
+
+#[test]
+fn flags_sponsored_card_and_open_ended_rates() {
+    let html = fixture("listing.html");
+    let companies = Scraper::new(&html)
+        .content()
+        .extract_companies_data()
+        .unwrap();
+
+    let company = &companies[1];
+    assert_eq!(company.title, "Oxide Works");
+    assert_eq!(company.profile_url, "https://clutch.co/profile/oxide-works");
+    assert!(company.is_sponsored);
+    assert_eq!(company.hourly_rate_min, None);
+    assert_eq!(company.hourly_rate_max, Some(25));
+    assert_eq!(company.min_project_size_usd, Some(10_000));
+}
+
+#[test]
+fn resolves_profile_urls_against_base_url() {
+    let html = fixture("listing.html").replace(
+        "https://clutch.co/profile/ferrous-labs\"",
+        "//clutch.co/profile/ferrous-labs\"",
+    );
+    let companies = Scraper::new(&html)
+        .content()
+        .with_base_url("https://clutch.co")
+        .extract_companies_data()
+        .unwrap();
+
+    assert_eq!(
+        companies[0].profile_url,
+        "https://clutch.co/profile/ferrous-labs"
+    );
+    assert_eq!(
+        companies[1].profile_url,
+        "https://clutch.co/profile/oxide-works"
+    );
+}
+
+#[test]
+fn schema_lists_every_serialized_field() {
+    let html = fixture("listing.html");
+    let companies = Scraper::new(&html)
+        .content()
+        .extract_companies_data()
+        .unwrap();
+    let company = serde_json::to_value(&companies[0]).unwrap();
+    let schema = CompanyData::json_schema();
+
+    let keys = |value: &serde_json::Value| {
+        let mut keys: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+        keys.sort();
+        keys
+    };
+    assert_eq!(keys(&company), keys(&schema["properties"]));
+    for nested in ["address", "rating"] {
+        assert_eq!(
+            keys(&company[nested]),
+            keys(&schema["properties"][nested]["properties"]),
+            "{} properties",
+            nested
+        );
+    }
+}
+
+#[test]
+fn analyzes_listing_pagination() {
+    let html = fixture("listing.html");
+    let page_info = Scraper::new(&html)
+        .page()
+        .with_current_path("/developers/rust")
+        .analyze()
+        .unwrap();
+
+    assert_eq!(page_info.current_page, 1);
+    assert_eq!(page_info.total_pages, Some(3));
+    assert_eq!(page_info.results_on_page, 2);
+    assert_eq!(page_info.total_results, Some(6));
+    assert_eq!(
+        page_info.next_url.as_deref(),
+        Some("https://clutch.co/developers/rust?page=1")
+    );
+}
+
+#[test]
+fn total_pages_looks_past_a_truncated_pager() {
+    let truncated = fixture("listing.html").replace(
+        r#"<li class="sg-pagination-v2-next">"#,
+        r#"<li class="sg-pagination-v2-ellipsis">...</li>
+    <li class="sg-pagination-v2-last"><a href="/developers/rust?page=86">87</a></li>
+    <li class="sg-pagination-v2-next">"#,
+    );
+    let page_info = Scraper::new(&truncated).page().analyze().unwrap();
+    assert_eq!(page_info.total_pages, Some(87));
+
+    // 1,234 results at 2 per page outnumber the pager
+    let many_results = truncated.replace("6 Companies", "1,234 Companies");
+    let page_info = Scraper::new(&many_results).page().analyze().unwrap();
+    assert_eq!(page_info.total_pages, Some(617));
+}
+
+#[test]
+fn missing_providers_list_is_an_error() {
+    let html = fixture("no_providers_list.html");
+    let content = Scraper::new(&html);
+    let content = content.content();
+
+    assert!(matches!(
+        content.check_providers_list(),
+        Err(ScraperError::ParseError(_))
+    ));
+    assert!(matches!(
+        content.extract_companies_data(),
+        Err(ScraperError::ParseError(_))
+    ));
+    assert_eq!(content.provider_count(), 0);
+}
+
+#[test]
+fn extract_companies_wraps_the_content_scraper() {
+    let companies = extract_companies(&fixture("listing.html")).unwrap();
+    assert_eq!(companies.len(), 2);
+    assert_eq!(companies[0].title, "Ferrous Labs");
+
+    assert!(matches!(
+        extract_companies(&fixture("no_providers_list.html")),
+        Err(AppError::Scraper(ScraperError::ParseError(_)))
+    ));
+}
+
+#[test]
+fn skips_card_missing_address() {
+    let html = fixture("missing_address.html");
+    let companies = Scraper::new(&html)
+        .content()
+        .extract_companies_data()
+        .unwrap();
+
+    assert_eq!(companies.len(), 1);
+    assert_eq!(companies[0].title, "Crab Systems");
+}
+
+#[test]
+fn keeps_card_with_partial_address() {
+    let html = fixture("missing_address.html").replace(
+        r#"<meta itemprop="telephone" content="+1 512 555 0100">"#,
+        "",
+    );
+    let companies = Scraper::new(&html)
+        .content()
+        .extract_companies_data()
+        .unwrap();
+
+    assert_eq!(companies.len(), 1);
+    let address = &companies[0].address;
+    assert_eq!(address.locality.as_deref(), Some("Austin"));
+    assert_eq!(address.telephone, None);
+}
+
+#[test]
+fn keeps_card_missing_highlights() {
+    let html = fixture("missing_address.html").replace(
+        r#"<div class="provider__highlights-item hourly-rate">$50 - $99 / hr</div>"#,
+        "",
+    );
+    let companies = Scraper::new(&html)
+        .content()
+        .extract_companies_data()
+        .unwrap();
+
+    assert_eq!(companies.len(), 1);
+    let company = &companies[0];
+    assert_eq!(company.hourly_rate, None);
+    assert_eq!(company.hourly_rate_min, None);
+    assert_eq!(company.employees.as_deref(), Some("2 - 9"));
+}
+
+#[test]
+fn low_extraction_ratio_fails_the_page() {
+    let html = fixture("missing_address.html");
+    let result = Scraper::new(&html)
+        .content()
+        .with_min_success_ratio(1.0)
+        .extract_companies_data();
+
+    assert!(matches!(
+        result,
+        Err(ScraperError::LowExtractionRatio {
+            extracted: 1,
+            found: 2
+        })
+    ));
+}
+
+#[test]
+fn page_number_round_trips_through_page_path() {
+    for page in [1, 2, 5] {
+        assert_eq!(page_number_of(&page_path("/developers/rust", page)), page);
+    }
+    assert_eq!(
+        page_number_of("https://clutch.co/developers/rust?page=4"),
+        5
+    );
+    assert_eq!(page_number_of("https://clutch.co/developers/rust"), 1);
+}
+
+#[test]
+fn falls_back_to_providers_list_without_id() {
+    let html = fixture("listing.html").replace(r#" id="providers__list""#, "");
+    let companies = Scraper::new(&html)
+        .content()
+        .extract_companies_data()
+        .unwrap();
+    assert_eq!(companies.len(), 2);
+    assert_eq!(companies[0].title, "Ferrous Labs");
+}
+
+#[test]
+fn parses_open_ended_and_bounded_ranges() {
+    assert_eq!(parse_numeric_range("1,000+"), (Some(1000), None));
+    assert_eq!(parse_numeric_range("$10,000+"), (Some(10000), None));
+    assert_eq!(parse_numeric_range("$50 - $99 / hr"), (Some(50), Some(99)));
+    assert_eq!(parse_numeric_range("< $25 / hr"), (None, Some(25)));
+    assert_eq!(parse_numeric_range("Undisclosed"), (None, None));
+}
+
+#[test]
+fn for_each_company_calls_back_once_per_extracted_card() {
+    let html = fixture("listing.html");
+    let mut titles = Vec::new();
+    Scraper::new(&html)
+        .content()
+        .for_each_company(|company| titles.push(company.title))
+        .unwrap();
+    assert_eq!(titles, ["Ferrous Labs", "Oxide Works"]);
+
+    // Cards that fail to extract never reach the callback
+    let html = fixture("missing_address.html");
+    let mut calls = 0;
+    Scraper::new(&html)
+        .content()
+        .for_each_company(|_| calls += 1)
+        .unwrap();
+    assert_eq!(calls, 1);
+}
+
+#[test]
+fn hashed_fields_match_across_records() {
+    let mut companies = extract_companies(&fixture("listing.html")).unwrap();
+    for company in &mut companies {
+        company.address.telephone = Some("+1 512 555 0100".to_string());
+    }
+    companies[1].address.locality = Some("Elsewhere".to_string());
+    let fields = ["telephone".to_string(), "locality".to_string()];
+    for company in &mut companies {
+        company.redact(&fields, RedactMode::Hash);
+    }
+
+    // Equal values hash equally, so redacted records can still be joined
+    let telephone = Some(utils::stable_hash("+1 512 555 0100"));
+    assert_eq!(companies[0].address.telephone, telephone);
+    assert_eq!(companies[1].address.telephone, telephone);
+    assert_ne!(companies[0].address.locality, companies[1].address.locality);
+    // FNV-1a reference value, so the hash can't drift between releases
+    assert_eq!(utils::stable_hash("a"), "af63dc4c8601ec8c");
+}
@@ -1,8 +1,15 @@
+use crate::config::StorageConfig;
 use crate::error::Result;
 pub use crate::log_info;
-use std::fs::{self, File};
-use std::io::Write;
+use crate::proxy::ProxyStats;
+use crate::scraper::{CompanyData, PageInfo};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 pub fn ensure_directory(dir: &str) -> Result<()> {
     if !Path::new(dir).exists() {
@@ -11,29 +18,111 @@ pub fn ensure_directory(dir: &str) -> Result<()> {
     Ok(())
 }
 
-pub fn save_html(content: &str, page_number: usize) -> Result<PathBuf> {
-    ensure_directory("local_html")?;
+pub fn html_path(html_dir: &str, page_number: usize, compress: bool) -> PathBuf {
+    let extension = if compress { "html.gz" } else { "html" };
+    Path::new(html_dir).join(format!("rust-page-{}.{}", page_number, extension))
+}
+
+// Path of a page saved by any run, compressed or not
+pub fn saved_html_path(html_dir: &str, page_number: usize) -> Option<PathBuf> {
+    [
+        html_path(html_dir, page_number, false),
+        html_path(html_dir, page_number, true),
+    ]
+    .into_iter()
+    .find(|path| path.exists())
+}
+
+fn is_html_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.ends_with(".html") || name.ends_with(".html.gz"))
+}
 
-    let filename = format!("local_html/rust-page-{}.html", page_number);
-    let path = PathBuf::from(&filename);
+pub fn save_html(content: &str, page_number: usize, storage: &StorageConfig) -> Result<PathBuf> {
+    ensure_directory(&storage.html_dir)?;
 
-    let mut file = File::create(&path)?;
-    file.write_all(content.as_bytes())?;
+    let path = html_path(&storage.html_dir, page_number, storage.compress);
+    if storage.compress {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content.as_bytes())?;
+        write_atomic(&path, &encoder.finish()?)?;
+    } else {
+        write_atomic(&path, content.as_bytes())?;
+    }
 
-    log_info!("[utils] Saved HTML content to {}", filename);
+    log_info!("[utils] Saved HTML content to {:?}", path);
     Ok(path)
 }
 
-pub fn read_html_files() -> Result<Vec<(PathBuf, String)>> {
-    ensure_directory("local_html")?;
+pub fn is_page_saved(html_dir: &str, page_number: usize) -> bool {
+    saved_html_path(html_dir, page_number).is_some()
+}
+
+// Reads a saved page, transparently decompressing `.gz` files
+pub fn read_html(path: &Path) -> Result<String> {
+    if path.extension().and_then(|s| s.to_str()) != Some("gz") {
+        return Ok(fs::read_to_string(path)?);
+    }
+
+    let mut content = String::new();
+    GzDecoder::new(File::open(path)?).read_to_string(&mut content)?;
+    Ok(content)
+}
+
+// Highest page number present in the HTML directory, used to resume a crawl
+pub fn highest_saved_page(html_dir: &str) -> Result<Option<usize>> {
+    if !Path::new(html_dir).exists() {
+        return Ok(None);
+    }
+
+    let mut highest = None;
+    for entry in fs::read_dir(html_dir)? {
+        let path = entry?.path();
+        if is_html_file(&path) {
+            highest = highest.max(extract_page_number(&path));
+        }
+    }
+    Ok(highest)
+}
+
+// Start paths from a categories file, one per line; blank lines and lines
+// starting with "#" are ignored
+pub fn read_category_paths(path: &Path) -> Result<Vec<String>> {
+    let contents = fs::read_to_string(path)?;
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+// Directory name for a category path, e.g. "/developers/rust" -> "developers-rust"
+pub fn category_slug(start_path: &str) -> String {
+    let path = start_path.split(['?', '#']).next().unwrap_or_default();
+    let slug = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        "root".to_string()
+    } else {
+        slug
+    }
+}
+
+pub fn read_html_files(html_dir: &str) -> Result<Vec<(PathBuf, String)>> {
+    ensure_directory(html_dir)?;
 
     let mut files = Vec::new();
-    for entry in fs::read_dir("local_html")? {
+    for entry in fs::read_dir(html_dir)? {
         let entry = entry?;
         let path = entry.path();
 
-        if path.extension().and_then(|s| s.to_str()) == Some("html") {
-            let content = fs::read_to_string(&path)?;
+        if is_html_file(&path) {
+            let content = read_html(&path)?;
             files.push((path, content));
         }
     }
@@ -57,14 +146,380 @@ fn extract_page_number(path: &Path) -> Option<usize> {
     })
 }
 
+// Progress written after each saved page so an interrupted run can pick up exactly where it stopped
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub base_url: String,
+    pub start_path: String,
+    pub current_path: String,
+    pub page: PageInfo,
+    pub saved_at: String,
+    pub proxy_stats: HashMap<String, ProxyStats>,
+}
+
+// Outcome of a whole run, written to `summary_file` for automation to check
+#[derive(Debug, Serialize)]
+pub struct RunSummary {
+    pub started_at: String,
+    pub finished_at: String,
+    // False when the run failed, including when fewer than
+    // `min_companies_for_success` companies were extracted
+    pub success: bool,
+    pub pages_downloaded: u64,
+    pub files_processed: usize,
+    pub companies_extracted: usize,
+    pub duplicates_dropped: usize,
+    pub working_proxies: usize,
+    pub dead_proxies: usize,
+}
+
+pub fn write_checkpoint(path: &Path, checkpoint: &Checkpoint) -> Result<()> {
+    save_json(checkpoint, path)
+}
+
+pub fn read_checkpoint(path: &Path) -> Result<Option<Checkpoint>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let contents = fs::read_to_string(path)?;
+    Ok(Some(serde_json::from_str(&contents)?))
+}
+
 pub fn save_json(data: &impl serde::Serialize, path: impl AsRef<Path>) -> Result<()> {
-    // Ensure the json_data directory exists
+    // Ensure the output directory exists
     if let Some(parent) = path.as_ref().parent() {
         fs::create_dir_all(parent)?;
     }
 
     let json_string = serde_json::to_string_pretty(data)?;
-    let mut file = File::create(path)?;
-    file.write_all(json_string.as_bytes())?;
+    write_atomic(path.as_ref(), json_string.as_bytes())
+}
+
+// JSON Schema describing each company in the JSON outputs
+pub fn write_schema(path: &Path) -> Result<()> {
+    save_json(&CompanyData::json_schema(), path)
+}
+
+// Writes to a temp file next to `path` and renames it into place, so an
+// interrupted write never leaves a truncated file at `path`
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let temp_path = path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()));
+
+    let result = File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(bytes)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp_path, path));
+
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+pub fn save_csv(companies: &[CompanyData], path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Only write the header when starting a fresh file, so appending
+    // page after page keeps a single header row at the top
+    let needs_header = fs::metadata(path).map(|m| m.len() == 0).unwrap_or(true);
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    if needs_header {
+        write_csv_row(&mut file, CompanyData::CSV_HEADERS.iter())?;
+    }
+
+    for company in companies {
+        write_csv_row(&mut file, company.to_csv_record().iter())?;
+    }
+
+    file.flush()?;
     Ok(())
 }
+
+pub fn append_jsonl(company: &CompanyData, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Serialize before touching the file so a failure never leaves a partial line
+    let mut line = serde_json::to_string(company)?;
+    line.push('\n');
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(line.as_bytes())?;
+    file.flush()?;
+    Ok(())
+}
+
+pub fn remove_file_if_exists(path: &Path) -> Result<()> {
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+fn write_csv_row<I, S>(writer: &mut impl Write, fields: I) -> Result<()>
+where
+    I: Iterator<Item = S>,
+    S: AsRef<str>,
+{
+    let line = fields
+        .map(|field| escape_csv_field(field.as_ref()))
+        .collect::<Vec<_>>()
+        .join(",");
+    writeln!(writer, "{}", line)?;
+    Ok(())
+}
+
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// FNV-1a is stable across runs and platforms, unlike std's RandomState hashing
+pub fn stable_hash(value: &str) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    let hash = value.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    });
+    format!("{:016x}", hash)
+}
+
+// Drops companies that share a profile URL, keeping the first one
+pub fn dedupe_companies(companies: Vec<CompanyData>) -> Vec<CompanyData> {
+    dedupe_unseen_companies(&mut HashSet::new(), companies)
+}
+
+// Like `dedupe_companies`, but also drops companies whose profile URL is
+// already in `seen`. Passing the same set for every page catches duplicates
+// across pages.
+pub fn dedupe_unseen_companies(
+    seen: &mut HashSet<String>,
+    companies: Vec<CompanyData>,
+) -> Vec<CompanyData> {
+    companies
+        .into_iter()
+        .filter(|company| seen.insert(company.profile_url().to_string()))
+        .collect()
+}
+
+// Differences between two runs' companies, keyed by profile URL
+#[derive(Debug, Default, Serialize)]
+pub struct CompanyDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<ModifiedCompany>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModifiedCompany {
+    pub profile_url: String,
+    // Top-level CompanyData fields whose serialized value changed
+    pub fields: Vec<String>,
+}
+
+impl CompanyDiff {
+    // Whether a company from the new run needs writing
+    pub fn is_changed(&self, profile_url: &str) -> bool {
+        self.added.iter().any(|url| url == profile_url)
+            || self
+                .modified
+                .iter()
+                .any(|modified| modified.profile_url == profile_url)
+    }
+}
+
+// Compares companies field by field through their JSON form. Lists keep the
+// order of `new` (and of `old` for removals).
+pub fn diff_companies(old: &[CompanyData], new: &[CompanyData]) -> CompanyDiff {
+    let to_fields = |company: &CompanyData| match serde_json::to_value(company) {
+        Ok(serde_json::Value::Object(fields)) => fields,
+        _ => serde_json::Map::new(),
+    };
+    let old_by_url: HashMap<&str, &CompanyData> = old
+        .iter()
+        .map(|company| (company.profile_url(), company))
+        .collect();
+    let new_urls: HashSet<&str> = new.iter().map(CompanyData::profile_url).collect();
+
+    let mut diff = CompanyDiff::default();
+    for company in new {
+        let Some(previous) = old_by_url.get(company.profile_url()) else {
+            diff.added.push(company.profile_url().to_string());
+            continue;
+        };
+
+        let (before, after) = (to_fields(previous), to_fields(company));
+        let fields: Vec<String> = after
+            .iter()
+            .filter(|(field, value)| before.get(*field) != Some(*value))
+            .map(|(field, _)| field.clone())
+            .chain(
+                before
+                    .keys()
+                    .filter(|field| !after.contains_key(*field))
+                    .cloned(),
+            )
+            .collect();
+        if !fields.is_empty() {
+            diff.modified.push(ModifiedCompany {
+                profile_url: company.profile_url().to_string(),
+                fields,
+            });
+        }
+    }
+    diff.removed = old
+        .iter()
+        .map(CompanyData::profile_url)
+        .filter(|url| !new_urls.contains(url))
+        .map(str::to_string)
+        .collect();
+    diff
+}
+
+pub fn now_rfc3339() -> String {
+    time::OffsetDateTime::now_utc()
+        .format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_default()
+}
+
+// Uniformly random duration in [min, max]; returns `min` when they are equal
+pub fn random_delay(min: Duration, max: Duration) -> Duration {
+    if max <= min {
+        return min;
+    }
+    min + (max - min).mul_f64(rand::random::<f64>())
+}
+
+// Exponential backoff (`base * 2^attempt`, capped at `max`) with jitter: half
+// of the delay is fixed and the other half random, so parallel retries spread out
+pub fn backoff(attempt: u32, base: Duration, max: Duration) -> Duration {
+    let exponential = base
+        .checked_mul(2u32.saturating_pow(attempt))
+        .unwrap_or(max)
+        .min(max);
+
+    let half = exponential / 2;
+    let jitter = half.mul_f64(rand::random::<f64>());
+    half + jitter
+}
+
+// Fresh, empty directory under the system temp dir for tests that write files
+#[cfg(test)]
+pub(crate) fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("clutch-scraper-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        backoff, dedupe_companies, dedupe_unseen_companies, diff_companies, scratch_dir,
+        write_atomic,
+    };
+    use crate::scraper::{extract_companies, CompanyData};
+    use std::collections::HashSet;
+    use std::fs;
+    use std::time::Duration;
+
+    fn listing_companies() -> Vec<CompanyData> {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/listing.html");
+        extract_companies(&fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn failed_atomic_write_leaves_no_partial_file() {
+        let dir = scratch_dir("utils-atomic");
+        // A directory at the final path makes the rename fail after the
+        // temp file was written
+        let target = dir.join("companies.json");
+        fs::create_dir(&target).unwrap();
+        fs::write(target.join("keep"), "").unwrap();
+
+        assert!(write_atomic(&target, b"{\"companies\": []}").is_err());
+
+        assert!(target.is_dir());
+        let leftovers: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(leftovers, vec!["companies.json"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn backoff_stays_under_max_and_grows_on_average() {
+        let base = Duration::from_millis(100);
+        let max = Duration::from_secs(5);
+        let mean = |attempt| {
+            let total: Duration = (0..200).map(|_| backoff(attempt, base, max)).sum();
+            total / 200
+        };
+
+        for attempt in 0..40 {
+            for _ in 0..50 {
+                assert!(backoff(attempt, base, max) <= max);
+            }
+        }
+        // Below the cap an attempt's shortest delay is the previous attempt's
+        // longest, so the means grow however the jitter falls
+        let means: Vec<Duration> = (0..6).map(mean).collect();
+        assert!(
+            means.windows(2).all(|pair| pair[0] < pair[1]),
+            "means did not grow: {:?}",
+            means
+        );
+    }
+
+    #[test]
+    fn dedupes_within_a_page_and_across_pages() {
+        let page = listing_companies();
+        let doubled: Vec<_> = page.iter().chain(&page).cloned().collect();
+        assert_eq!(dedupe_companies(doubled).len(), page.len());
+
+        let mut seen = HashSet::new();
+        assert_eq!(
+            dedupe_unseen_companies(&mut seen, page.clone()).len(),
+            page.len()
+        );
+        assert!(dedupe_unseen_companies(&mut seen, page).is_empty());
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_modified_companies() {
+        let old = listing_companies();
+        let mut new = listing_companies();
+
+        let removed = new.pop().unwrap();
+        new[0].hourly_rate = Some("$150 - $199 / hr".to_string());
+        let mut added = removed.clone();
+        added.profile_url = "https://clutch.co/profile/new-company".to_string();
+        new.push(added);
+
+        let diff = diff_companies(&old, &new);
+        assert_eq!(diff.added, ["https://clutch.co/profile/new-company"]);
+        assert_eq!(diff.removed, [removed.profile_url.as_str()]);
+        assert_eq!(diff.modified.len(), 1);
+        assert_eq!(diff.modified[0].profile_url, new[0].profile_url);
+        assert_eq!(diff.modified[0].fields, ["hourly_rate"]);
+        assert!(!diff.is_changed(&removed.profile_url));
+    }
+}